@@ -0,0 +1,32 @@
+//! `cargo bench` entry point. Runs every day with the default settings; an
+//! argument such as `cargo bench -- 3..7` limits it to some days. Use the
+//! `bench` subcommand for baselines and other options.
+
+use aoc_2022::bench::{self, Config};
+use aoc_2022::days::{DaySelection, Part};
+use aoc_2022::inputs::Inputs;
+use std::env;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    // Cargo passes `--bench`, so only look at other arguments.
+    let selection = match env::args().skip(1).find(|arg| !arg.starts_with("--")) {
+        Some(days) => match days.parse::<DaySelection>() {
+            Ok(selection) => selection,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => DaySelection::all(),
+    };
+    let report = bench::run(
+        &selection,
+        &Part::BOTH,
+        &Inputs::from_env(),
+        &Config::default(),
+        None,
+    );
+    println!("{}", report);
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,63 @@
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// The result of solving one part of a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+    /// Multi-line answers such as a rendered CRT screen, one entry per row.
+    Grid(Vec<String>),
+    /// The input has no answer for this part.
+    Unsolved,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(num) => write!(f, "{}", num),
+            Answer::Text(text) => write!(f, "{}", text),
+            Answer::Grid(rows) => write!(f, "{}", rows.join("\n")),
+            Answer::Unsolved => write!(f, "unsolved"),
+        }
+    }
+}
+
+/// Numbers and text serialize as themselves, grids as an array of rows and
+/// unsolved parts as `null`.
+impl Serialize for Answer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Answer::Number(num) => serializer.serialize_i64(*num),
+            Answer::Text(text) => serializer.serialize_str(text),
+            Answer::Grid(rows) => rows.serialize(serializer),
+            Answer::Unsolved => serializer.serialize_none(),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<Vec<Vec<char>>> for Answer {
+    fn from(value: Vec<Vec<char>>) -> Self {
+        Answer::Grid(value.iter().map(|row| row.iter().collect()).collect())
+    }
+}
+
+macro_rules! impl_from_number {
+    ($($num:ty),*) => {
+        $(
+            impl From<$num> for Answer {
+                fn from(value: $num) -> Self {
+                    Answer::Number(i64::try_from(value).expect("answer does not fit in an i64"))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_number!(i32, i64, u32, u64, usize);
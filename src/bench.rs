@@ -0,0 +1,330 @@
+use crate::days::{Day, DaySelection, Part};
+use crate::error::{Error, Result};
+use crate::inputs::Inputs;
+use crate::util;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    /// How long to run each benchmark before measuring, to warm caches and
+    /// estimate how many iterations fit in a sample.
+    pub warmup: Duration,
+    /// Roughly how long to spend measuring each benchmark.
+    pub measurement: Duration,
+    pub samples: u32,
+    /// Relative slowdown of the median, e.g. `0.05`, above which a benchmark
+    /// counts as a regression against the baseline.
+    pub threshold: f64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            warmup: Duration::from_millis(300),
+            measurement: Duration::from_secs(1),
+            samples: 50,
+            threshold: 0.05,
+        }
+    }
+}
+
+/// What is being timed for a day: parsing its input or solving one part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Parse,
+    Solve(Part),
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Parse => write!(f, "parse"),
+            Target::Solve(part) => write!(f, "part{}", part.number()),
+        }
+    }
+}
+
+/// Per-iteration times of a benchmark in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    pub median: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Stats {
+    fn from_samples(samples: &mut [f64]) -> Self {
+        samples.sort_by(f64::total_cmp);
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+        let mid = samples.len() / 2;
+        let median = if samples.len().is_multiple_of(2) {
+            (samples[mid - 1] + samples[mid]) / 2.0
+        } else {
+            samples[mid]
+        };
+        Self {
+            median,
+            mean,
+            std_dev: variance.sqrt(),
+            min: samples[0],
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Runs `f` for the warmup period, then takes `config.samples` samples of
+/// as many iterations as fit in the measurement time.
+pub fn measure<T>(config: &Config, mut f: impl FnMut() -> T) -> Stats {
+    let start = Instant::now();
+    let mut warmup_iters = 0u64;
+    while warmup_iters == 0 || start.elapsed() < config.warmup {
+        black_box(f());
+        warmup_iters += 1;
+    }
+    let per_iter = start.elapsed().as_secs_f64() / warmup_iters as f64;
+
+    let samples = config.samples.max(1);
+    let sample_time = config.measurement.as_secs_f64() / samples as f64;
+    let iters = ((sample_time / per_iter) as u64).max(1);
+    let mut times: Vec<f64> = (0..samples)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..iters {
+                black_box(f());
+            }
+            start.elapsed().as_nanos() as f64 / iters as f64
+        })
+        .collect();
+    Stats::from_samples(&mut times)
+}
+
+/// Saved results to compare later runs against, keyed by e.g. `day07/part2`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Baseline(BTreeMap<String, Stats>);
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = util::read_input(path)?;
+        serde_json::from_str(&content).map_err(|err| Error::Baseline {
+            path: path.display().to_string(),
+            reason: err.to_string(),
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).expect("baseline is valid JSON");
+        fs::write(path, content + "\n").map_err(|source| Error::Write {
+            path: path.display().to_string(),
+            source,
+        })
+    }
+
+    fn get(&self, day: u8, target: Target) -> Option<&Stats> {
+        self.0.get(&key(day, target))
+    }
+}
+
+fn key(day: u8, target: Target) -> String {
+    format!("day{:02}/{}", day, target)
+}
+
+#[derive(Debug, Clone)]
+pub struct Benchmark {
+    pub day: u8,
+    pub target: Target,
+    pub stats: Stats,
+    /// Relative change of the median against the baseline, if it had this
+    /// benchmark.
+    pub change: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub benchmarks: Vec<Benchmark>,
+    /// Days that could not be benchmarked, with the reason.
+    pub skipped: Vec<(u8, String)>,
+    threshold: f64,
+}
+
+impl Report {
+    pub fn regressions(&self) -> impl Iterator<Item = &Benchmark> {
+        self.benchmarks
+            .iter()
+            .filter(|bench| bench.change.is_some_and(|change| change > self.threshold))
+    }
+
+    /// The results of this run, to be saved as a baseline for later runs.
+    pub fn baseline(&self) -> Baseline {
+        Baseline(
+            self.benchmarks
+                .iter()
+                .map(|bench| (key(bench.day, bench.target), bench.stats))
+                .collect(),
+        )
+    }
+}
+
+/// Benchmarks parsing and the selected parts of each day, printing each
+/// result as it finishes. Days without a readable input are skipped.
+pub fn run(
+    selection: &DaySelection,
+    parts: &[Part],
+    inputs: &Inputs,
+    config: &Config,
+    baseline: Option<&Baseline>,
+) -> Report {
+    let mut report = Report {
+        threshold: config.threshold,
+        ..Report::default()
+    };
+    for day in selection.days() {
+        match bench_day(day, parts, inputs, config, baseline, &mut report) {
+            Ok(()) => {}
+            Err(err) => {
+                println!("day{:02}: skipped, {}", day.number, err);
+                report.skipped.push((day.number, err.to_string()));
+            }
+        }
+    }
+    report
+}
+
+fn bench_day(
+    day: &Day,
+    parts: &[Part],
+    inputs: &Inputs,
+    config: &Config,
+    baseline: Option<&Baseline>,
+    report: &mut Report,
+) -> Result<()> {
+    let raw_input = inputs.read(day.number)?;
+    let input = day.parse(&raw_input)?;
+    let targets =
+        std::iter::once(Target::Parse).chain(parts.iter().map(|&part| Target::Solve(part)));
+    for target in targets {
+        let stats = match target {
+            Target::Parse => measure(config, || day.parse(&raw_input)),
+            Target::Solve(part) => measure(config, || day.solution.solve(&input, part)),
+        };
+        let change = baseline
+            .and_then(|baseline| baseline.get(day.number, target))
+            .map(|old| (stats.median - old.median) / old.median);
+        let bench = Benchmark {
+            day: day.number,
+            target,
+            stats,
+            change,
+        };
+        println!("{}", Line(&bench, config.threshold));
+        report.benchmarks.push(bench);
+    }
+    Ok(())
+}
+
+/// A benchmark's result line, flagging changes beyond the threshold.
+struct Line<'a>(&'a Benchmark, f64);
+
+impl fmt::Display for Line<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Line(bench, threshold) = *self;
+        let stats = &bench.stats;
+        write!(
+            f,
+            "{:<14} median {:>10}  min {:>10}  max {:>10}  ± {:>9}",
+            key(bench.day, bench.target),
+            nanos(stats.median),
+            nanos(stats.min),
+            nanos(stats.max),
+            nanos(stats.std_dev),
+        )?;
+        if let Some(change) = bench.change {
+            write!(f, "  {:>+6.1}%", change * 100.0)?;
+            if change > threshold {
+                write!(f, " REGRESSED")?;
+            } else if change < -threshold {
+                write!(f, " improved")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn nanos(ns: f64) -> String {
+    format!("{:.2?}", Duration::from_secs_f64(ns / 1e9))
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} benchmarks, {} regressions, {} days skipped",
+            self.benchmarks.len(),
+            self.regressions().count(),
+            self.skipped.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::from_samples(&mut [4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(stats.median, 2.5);
+        assert_eq!(stats.mean, 2.5);
+        assert_eq!((stats.min, stats.max), (1.0, 4.0));
+        assert!((stats.std_dev - 1.25f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_measure() {
+        let config = Config {
+            warmup: Duration::ZERO,
+            measurement: Duration::from_millis(1),
+            samples: 5,
+            ..Config::default()
+        };
+        let mut calls = 0;
+        let stats = measure(&config, || calls += 1);
+        assert!(calls >= 6);
+        assert!(stats.min <= stats.median && stats.median <= stats.max);
+    }
+
+    #[test]
+    fn test_regressions() {
+        let stats = Stats::from_samples(&mut [1.0]);
+        let bench = |change| Benchmark {
+            day: 1,
+            target: Target::Solve(Part::One),
+            stats,
+            change,
+        };
+        let report = Report {
+            benchmarks: vec![
+                bench(Some(0.5)),
+                bench(Some(0.01)),
+                bench(Some(-0.5)),
+                bench(None),
+            ],
+            skipped: Vec::new(),
+            threshold: 0.05,
+        };
+        assert_eq!(report.regressions().count(), 1);
+        assert_eq!(
+            report.baseline().get(1, Target::Solve(Part::One)),
+            Some(&stats)
+        );
+    }
+}
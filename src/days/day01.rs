@@ -1,18 +1,32 @@
-use crate::util;
+use super::Solution;
+use crate::error::{parse_number, ParseError};
 
-pub fn run() {
-    let raw_input = util::read_input("inputs/day01.txt").unwrap();
-    let input = process(raw_input);
-    println!("part 1: {}", part_1(&input));
-    println!("part 2: {}", part_2(&input));
+pub struct Day01;
+
+impl Solution for Day01 {
+    type Input = Vec<u64>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        process(input)
+    }
+
+    fn part_1(&self, input: &Self::Input) -> u64 {
+        part_1(input)
+    }
+
+    fn part_2(&self, input: &Self::Input) -> u64 {
+        part_2(input)
+    }
 }
 
-fn process(input: String) -> Vec<u64> {
+fn process(input: &str) -> Result<Vec<u64>, ParseError<'_>> {
     input
         .split("\n\n")
         .map(|elf| {
             elf.lines()
-                .map(|calories| calories.parse::<u64>().unwrap())
+                .map(|calories| parse_number::<u64>(calories, "a number of calories"))
                 .sum()
         })
         .collect()
@@ -32,19 +46,10 @@ fn part_2(input: &[u64]) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-
-    use test::Bencher;
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) {
-        let input = process(util::read_input("inputs/day01.txt").unwrap());
-        b.iter(|| part_1(&input));
-    }
+    use crate::days::check_example;
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) {
-        let input = process(util::read_input("inputs/day01.txt").unwrap());
-        b.iter(|| part_2(&input));
+    #[test]
+    fn test_example() {
+        check_example(&Day01, include_str!("examples/day01.txt"), 24000, 45000);
     }
 }
@@ -1,11 +1,25 @@
-use crate::util;
-
-pub fn run() {
-    let raw_input = util::read_input("inputs/day02.txt").unwrap();
-    let input = process(raw_input.clone());
-    println!("part 1: {}", part_1(&input));
-    let p2_input = process_p2(raw_input);
-    println!("part 2: {}", part_1(&p2_input));
+use super::Solution;
+use crate::error::ParseError;
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    /// The rounds read as two plays, and as a play and a desired outcome.
+    type Input = (Vec<Round>, Vec<Round>);
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        Ok((process(input)?, process_p2(input)?))
+    }
+
+    fn part_1(&self, input: &Self::Input) -> u64 {
+        part_1(&input.0)
+    }
+
+    fn part_2(&self, input: &Self::Input) -> u64 {
+        part_1(&input.1)
+    }
 }
 
 fn part_1(input: &[Round]) -> u64 {
@@ -15,7 +29,7 @@ fn part_1(input: &[Round]) -> u64 {
 }
 
 #[derive(Debug)]
-enum Selection {
+pub enum Selection {
     Rock,
     Paper,
     Scissors,
@@ -28,48 +42,58 @@ enum Outcome {
     Win,
 }
 
-impl From<&str> for Outcome {
-    fn from(value: &str) -> Self {
+impl<'a> TryFrom<&'a str> for Outcome {
+    type Error = ParseError<'a>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
         match value {
-            "X" => Outcome::Lose,
-            "Y" => Outcome::Draw,
-            "Z" => Outcome::Win,
-            _ => panic!("unreachable"),
+            "X" => Ok(Outcome::Lose),
+            "Y" => Ok(Outcome::Draw),
+            "Z" => Ok(Outcome::Win),
+            _ => Err(ParseError::new(value, "expected an outcome of X, Y or Z")),
         }
     }
 }
 
 type Round = (Selection, Selection);
 
-impl From<&str> for Selection {
-    fn from(value: &str) -> Self {
+impl<'a> TryFrom<&'a str> for Selection {
+    type Error = ParseError<'a>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
         match value {
-            "A" | "X" => Selection::Rock,
-            "B" | "Y" => Selection::Paper,
-            "C" | "Z" => Selection::Scissors,
-            _ => panic!("unreachable"),
+            "A" | "X" => Ok(Selection::Rock),
+            "B" | "Y" => Ok(Selection::Paper),
+            "C" | "Z" => Ok(Selection::Scissors),
+            _ => Err(ParseError::new(value, "expected a play of A, B or C")),
         }
     }
 }
 
-fn process(input: String) -> Vec<Round> {
+fn split_round(r_str: &str) -> Result<(&str, &str), ParseError<'_>> {
+    r_str
+        .split_once(' ')
+        .ok_or_else(|| ParseError::new(r_str, "expected two columns separated by a space"))
+}
+
+fn process(input: &str) -> Result<Vec<Round>, ParseError<'_>> {
     input
         .lines()
         .map(|r_str| {
-            let (other, me) = r_str.split_once(' ').unwrap();
-            (Selection::from(other), Selection::from(me))
+            let (other, me) = split_round(r_str)?;
+            Ok((Selection::try_from(other)?, Selection::try_from(me)?))
         })
         .collect()
 }
 
-fn process_p2(input: String) -> Vec<Round> {
+fn process_p2(input: &str) -> Result<Vec<Round>, ParseError<'_>> {
     input
         .lines()
         .map(|r_str| {
-            let (play, desired_outcome) = r_str.split_once(' ').unwrap();
-            let opponent_selection = Selection::from(play);
-            let desired_outcome = Outcome::from(desired_outcome);
-            calculate_your_play((opponent_selection, desired_outcome))
+            let (play, desired_outcome) = split_round(r_str)?;
+            let opponent_selection = Selection::try_from(play)?;
+            let desired_outcome = Outcome::try_from(desired_outcome)?;
+            Ok(calculate_your_play((opponent_selection, desired_outcome)))
         })
         .collect()
 }
@@ -105,18 +129,10 @@ fn calculate_your_play(desired: (Selection, Outcome)) -> Round {
 #[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-    use test::Bencher;
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) {
-        let input = process(util::read_input("inputs/day02.txt").unwrap());
-        b.iter(|| part_1(&input));
-    }
+    use crate::days::check_example;
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) {
-        let input = process_p2(util::read_input("inputs/day02.txt").unwrap());
-        b.iter(|| part_1(&input));
+    #[test]
+    fn test_example() {
+        check_example(&Day02, include_str!("examples/day02.txt"), 15, 12);
     }
 }
@@ -1,11 +1,27 @@
-use crate::util;
+use super::Solution;
+use crate::error::ParseError;
 use itertools::Itertools;
 use std::collections::HashSet;
 
-pub fn run() {
-    let input = util::read_input("inputs/day03.txt").unwrap();
-    println!("part 1: {}", part_1(input.clone()));
-    println!("part 2: {}", part_2(input));
+pub struct Day03;
+
+impl Solution for Day03 {
+    type Input = String;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        validate(input)?;
+        Ok(input.to_owned())
+    }
+
+    fn part_1(&self, input: &Self::Input) -> u32 {
+        part_1(input)
+    }
+
+    fn part_2(&self, input: &Self::Input) -> u32 {
+        part_2(input)
+    }
 }
 
 #[derive(Debug)]
@@ -22,19 +38,65 @@ impl Rucksack {
         Rucksack { first, second }
     }
 
-    fn common(&self) -> char {
-        self.first
-            .intersection(&self.second)
-            .next()
-            .copied()
-            .unwrap()
+    fn common(&self) -> Option<char> {
+        self.first.intersection(&self.second).next().copied()
     }
 }
 
-fn part_1(input: String) -> u32 {
+/// Checks everything the solvers rely on, so that they cannot panic: item
+/// letters, rucksacks that split evenly and share an item between their
+/// halves, and whole groups of three with an item in common.
+fn validate(input: &str) -> Result<(), ParseError<'_>> {
+    let lines: Vec<&str> = input.lines().collect();
+    for line in &lines {
+        if let Some((i, c)) = line.char_indices().find(|(_, c)| !c.is_ascii_alphabetic()) {
+            return Err(ParseError::new(
+                &line[i..i + c.len_utf8()],
+                "expected an item letter",
+            ));
+        }
+        if line.len() % 2 != 0 {
+            return Err(ParseError::new(
+                line,
+                "expected an even number of items, half in each compartment",
+            ));
+        }
+        if Rucksack::new(line).common().is_none() {
+            return Err(ParseError::new(
+                line,
+                "expected an item in both compartments",
+            ));
+        }
+    }
+    for group in lines.chunks(3) {
+        match group {
+            [a, b, c] => {
+                if Group::new(a, b, c).common().is_none() {
+                    return Err(ParseError::new(
+                        a,
+                        "expected an item common to this group of three rucksacks",
+                    ));
+                }
+            }
+            _ => {
+                return Err(ParseError::new(
+                    group[0],
+                    "expected the rucksacks to come in groups of three",
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn part_1(input: &str) -> u32 {
     input
         .lines()
-        .map(|ruck_str| Rucksack::new(ruck_str).common())
+        .map(|ruck_str| {
+            Rucksack::new(ruck_str)
+                .common()
+                .expect("parse checks for a shared item")
+        })
         .fold(0, |total_priority, shared| {
             total_priority + priority(shared)
         })
@@ -62,18 +124,22 @@ impl Group {
         Group { a, b, c }
     }
 
-    fn common(&self) -> char {
+    fn common(&self) -> Option<char> {
         let a_b_shared: HashSet<char> = self.a.intersection(&self.b).copied().collect();
-        a_b_shared.intersection(&self.c).next().copied().unwrap()
+        a_b_shared.intersection(&self.c).next().copied()
     }
 }
 
-fn part_2(input: String) -> u32 {
+fn part_2(input: &str) -> u32 {
     // chunks will on work on a Vec type, not String
     let lines: Vec<&str> = input.lines().collect_vec();
     lines
         .chunks(3)
-        .map(|group_lines| Group::new(group_lines[0], group_lines[1], group_lines[2]).common())
+        .map(|group_lines| {
+            Group::new(group_lines[0], group_lines[1], group_lines[2])
+                .common()
+                .expect("parse checks for whole groups with a common item")
+        })
         .fold(0, |total_priority, shared| {
             total_priority + priority(shared)
         })
@@ -82,31 +148,43 @@ fn part_2(input: String) -> u32 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-    use test::Bencher;
+    use crate::days::check_example;
 
     #[test]
     fn test_part_1() {
-        let test_input = String::from(
-            "vJrwpWtwJgWrhcsFMMfFFhFp
+        let test_input = "vJrwpWtwJgWrhcsFMMfFFhFp
 jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
 PmmdzqPrVvPwwTWBwg
 wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
 ttgJtRGJQctTZtZT
-CrZsJsPPZsGzwwsLwLmpwMDw",
-        );
+CrZsJsPPZsGzwwsLwLmpwMDw";
         assert_eq!(part_1(test_input), 157);
     }
 
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) {
-        let input = util::read_input("inputs/day03.txt").unwrap();
-        b.iter(|| part_1(input.clone()));
+    #[test]
+    fn test_example() {
+        check_example(&Day03, include_str!("examples/day03.txt"), 157, 70);
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) {
-        let input = util::read_input("inputs/day03.txt").unwrap();
-        b.iter(|| part_2(input.clone()));
+    #[test]
+    fn test_validate() {
+        let reason = |input| validate(input).unwrap_err().reason().to_string();
+        assert_eq!(reason("ab1b"), "expected an item letter");
+        assert_eq!(
+            reason("aba"),
+            "expected an even number of items, half in each compartment"
+        );
+        assert_eq!(reason("ab\ncd"), "expected an item in both compartments");
+        assert_eq!(
+            reason("aa\nbb\ncc"),
+            "expected an item common to this group of three rucksacks"
+        );
+        assert_eq!(
+            reason("aa\naa\naa\nbb"),
+            "expected the rucksacks to come in groups of three"
+        );
+        let input = "aa\naa\naa\nbb";
+        let err = validate(input).unwrap_err().locate(3, input);
+        assert!(err.to_string().starts_with("day 03, line 4, column 1:"));
     }
 }
@@ -1,31 +1,52 @@
 use std::collections::HashSet;
 
-use crate::util;
+use super::Solution;
+use crate::error::{parse_number, ParseError};
 
-pub fn run() {
-    let raw_input = util::read_input("inputs/day04.txt").unwrap();
-    let input = process(raw_input);
-    println!("part 1: {}", part_1(&input));
-    println!("part 2: {}", part_2(&input));
+pub struct Day04;
+
+impl Solution for Day04 {
+    type Input = Vec<Pair>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        process(input)
+    }
+
+    fn part_1(&self, input: &Self::Input) -> usize {
+        part_1(input)
+    }
+
+    fn part_2(&self, input: &Self::Input) -> usize {
+        part_2(input)
+    }
 }
 
 type Pair = ((u32, u32), (u32, u32));
 
-fn process(input: String) -> Vec<Pair> {
+fn process(input: &str) -> Result<Vec<Pair>, ParseError<'_>> {
     input
         .lines()
         .map(|pair_str| {
-            let (first, second) = pair_str.split_once(',').unwrap();
-            let (first_start, first_end) = first.split_once('-').unwrap();
-            let (second_start, second_end) = second.split_once('-').unwrap();
-            (
-                (first_start.parse().unwrap(), first_end.parse().unwrap()),
-                (second_start.parse().unwrap(), second_end.parse().unwrap()),
-            )
+            let (first, second) = pair_str
+                .split_once(',')
+                .ok_or_else(|| ParseError::new(pair_str, "expected two ranges separated by `,`"))?;
+            Ok((parse_range(first)?, parse_range(second)?))
         })
         .collect()
 }
 
+fn parse_range(range: &str) -> Result<(u32, u32), ParseError<'_>> {
+    let (start, end) = range
+        .split_once('-')
+        .ok_or_else(|| ParseError::new(range, "expected a range like `2-4`"))?;
+    Ok((
+        parse_number(start, "a section number")?,
+        parse_number(end, "a section number")?,
+    ))
+}
+
 fn part_1(pairs: &[Pair]) -> usize {
     pairs
         .iter()
@@ -51,19 +72,10 @@ fn part_2(pairs: &[Pair]) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-
-    use test::Bencher;
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) {
-        let input = process(util::read_input("inputs/day04.txt").unwrap());
-        b.iter(|| part_1(&input));
-    }
+    use crate::days::check_example;
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) {
-        let input = process(util::read_input("inputs/day04.txt").unwrap());
-        b.iter(|| part_2(&input));
+    #[test]
+    fn test_example() {
+        check_example(&Day04, include_str!("examples/day04.txt"), 2, 4);
     }
 }
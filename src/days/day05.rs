@@ -1,115 +1,535 @@
-use std::collections::VecDeque;
+use super::Solution;
+use crate::error::{parse_number, ParseError};
+use itertools::Itertools;
+use std::fmt;
 
-use crate::util;
+pub struct Day05;
 
-pub fn run() {
-    let raw_input = util::read_input("inputs/day05.txt").unwrap();
-    let (mut cargo, instructions) = parse(raw_input);
+impl Solution for Day05 {
+    type Input = (Cargo, Vec<MoveInstruction>);
+    type Part1 = String;
+    type Part2 = String;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        parse(input)
+    }
+
+    fn part_1(&self, input: &Self::Input) -> String {
+        rearrange(input, &CrateMover9000)
+    }
+
+    fn part_2(&self, input: &Self::Input) -> String {
+        rearrange(input, &CrateMover9001)
+    }
+}
+
+fn rearrange((cargo, instructions): &(Cargo, Vec<MoveInstruction>), crane: &dyn Crane) -> String {
+    let mut cargo = cargo.clone();
     instructions
         .iter()
-        .for_each(|instruction| cargo.execute_move(instruction));
-    println!("part 1: {}", cargo.tops());
+        .for_each(|instruction| cargo.execute_move(instruction, crane));
+    cargo.tops()
 }
 
-fn parse(input: String) -> (Cargo, Vec<MoveInstruction>) {
-    let (start, instructions) = input.split_once("\n\n").unwrap();
-    let cargo = parse_starting_pos(start);
-    let instructions = instructions.lines().map(MoveInstruction::from).collect();
-    (cargo, instructions)
+/// A crane model, which decides what order the crates of a single move end
+/// up in.
+pub trait Crane {
+    /// Rearranges `lifted`, given bottom to top as they were on the source
+    /// stack, into the order they are put down on the destination.
+    fn arrange(&self, lifted: &mut [String]);
 }
 
-fn parse_starting_pos(input: &str) -> Cargo {
+/// Moves crates one at a time, so they land in reverse order.
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn arrange(&self, lifted: &mut [String]) {
+        lifted.reverse();
+    }
+}
+
+/// Moves several crates at once, keeping their order.
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn arrange(&self, _lifted: &mut [String]) {}
+}
+
+fn parse(input: &str) -> Result<(Cargo, Vec<MoveInstruction>), ParseError<'_>> {
+    let (start, instructions) = input.split_once("\n\n").ok_or_else(|| {
+        ParseError::after(
+            input,
+            "expected a blank line between the drawing and the moves",
+        )
+    })?;
+    let cargo = parse_starting_pos(start)?;
+    let instructions = instructions
+        .lines()
+        .map(|line| {
+            let instruction = MoveInstruction::try_from(line)?;
+            if instruction.source >= cargo.stacks.len()
+                || instruction.destination >= cargo.stacks.len()
+            {
+                return Err(ParseError::new(
+                    line,
+                    format!("expected stacks between 1 and {}", cargo.stacks.len()),
+                ));
+            }
+            Ok(instruction)
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((cargo, instructions))
+}
+
+/// Parses a drawing of the stacks such as:
+///
+/// ```text
+///     [D]
+/// [N] [C]
+/// [Z] [M] [P]
+///  1   2   3
+/// ```
+///
+/// Each crate belongs to the stack whose number is below it, so stacks can
+/// be any width and labels any length, as drawn by [`Cargo`]'s `Display`.
+fn parse_starting_pos(input: &str) -> Result<Cargo, ParseError<'_>> {
     let mut stack_data = input.lines().rev();
-    let num_stacks = stack_data
+    let numbers = stack_data
         .next()
-        .unwrap()
-        .trim()
-        .split_ascii_whitespace()
-        .count();
+        .ok_or_else(|| ParseError::new(input, "expected a drawing of the stacks"))?;
+    let mut columns = Vec::new();
+    for (i, (span, number)) in tokens(numbers).enumerate() {
+        if parse_number::<usize>(number, "a stack number")? != i + 1 {
+            return Err(ParseError::new(number, format!("expected stack {}", i + 1)));
+        }
+        columns.push(span);
+    }
 
-    let mut stacks: Vec<Vec<char>> = vec![Vec::new(); num_stacks];
+    let mut stacks: Vec<Vec<String>> = vec![Vec::new(); columns.len()];
     for row in stack_data {
-        for (col, c) in row.chars().skip(1).enumerate() {
-            match c {
-                'A'..='Z' => stacks[col / 4].push(c),
-                _ => continue,
-            }
+        for ((start, end), token) in tokens(row) {
+            let label = token
+                .strip_prefix('[')
+                .and_then(|token| token.strip_suffix(']'))
+                .filter(|label| !label.is_empty())
+                .ok_or_else(|| ParseError::new(token, "expected a crate like `[A]`"))?;
+            let stack = columns
+                .iter()
+                .position(|&(col_start, col_end)| start < col_end && col_start < end)
+                .ok_or_else(|| {
+                    ParseError::new(token, "expected crates only above a stack number")
+                })?;
+            stacks[stack].push(label.to_owned());
         }
     }
-    Cargo::new(stacks)
+    Ok(Cargo::new(stacks))
 }
 
-#[derive(Debug, Clone)]
-struct Cargo {
-    stacks: Vec<Vec<char>>,
+/// Whitespace separated tokens of `line` with the columns they span.
+fn tokens(line: &str) -> impl Iterator<Item = ((usize, usize), &str)> {
+    line.split_whitespace().map(move |token| {
+        let offset = token.as_ptr() as usize - line.as_ptr() as usize;
+        let start = line[..offset].chars().count();
+        ((start, start + token.chars().count()), token)
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cargo {
+    /// Crate labels in each stack, bottom to top.
+    stacks: Vec<Vec<String>>,
 }
 
 impl Cargo {
-    fn new(stacks: Vec<Vec<char>>) -> Self {
+    fn new(stacks: Vec<Vec<String>>) -> Self {
         Self { stacks }
     }
 
-    fn execute_move(&mut self, instruction: &MoveInstruction) {
-        let mut temp_storage = VecDeque::new();
-        for _ in 0..instruction.amount {
-            if let Some(item) = self.stacks[instruction.source].pop() {
-                temp_storage.push_front(item);
-            } else {
-                break;
-            }
-        }
-        self.stacks[instruction.destination].extend(temp_storage.iter().copied());
+    /// Crate labels in each stack, bottom to top.
+    pub fn stacks(&self) -> &[Vec<String>] {
+        &self.stacks
+    }
+
+    /// Moves as many of the requested crates as the source stack holds.
+    fn execute_move(&mut self, instruction: &MoveInstruction, crane: &dyn Crane) {
+        let mut lifted = self.lift(instruction);
+        crane.arrange(&mut lifted);
+        self.stacks[instruction.destination].extend(lifted);
     }
 
-    fn tops(&self) -> String {
+    /// Takes up to `instruction.amount` crates off the source stack, bottom
+    /// to top.
+    fn lift(&mut self, instruction: &MoveInstruction) -> Vec<String> {
+        let source = &mut self.stacks[instruction.source];
+        let amount = (instruction.amount as usize).min(source.len());
+        source.split_off(source.len() - amount)
+    }
+
+    /// The crate on top of each stack, with a space for an empty stack so
+    /// that the rest stay in position.
+    pub fn tops(&self) -> String {
         let mut message = String::new();
         for stack in self.stacks.iter() {
-            message.push(stack.last().copied().unwrap())
+            message.push_str(stack.last().map_or(" ", String::as_str))
         }
         message
     }
 }
 
+/// Draws the stacks the way the puzzle input does. Columns are widened to fit
+/// the longest label or stack number, and trailing spaces are left off.
+impl fmt::Display for Cargo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .stacks
+            .iter()
+            .flatten()
+            .map(|label| label.chars().count() + 2)
+            .chain([3, self.stacks.len().to_string().len()])
+            .max()
+            .unwrap();
+        let height = self.stacks.iter().map(Vec::len).max().unwrap_or(0);
+        let row = |cells: Vec<String>| {
+            let line = cells
+                .iter()
+                .map(|cell| format!("{:^width$}", cell, width = width))
+                .join(" ");
+            line.trim_end().to_owned()
+        };
+        for level in (0..height).rev() {
+            let cells = self
+                .stacks
+                .iter()
+                .map(|stack| {
+                    stack
+                        .get(level)
+                        .map_or(String::new(), |label| format!("[{}]", label))
+                })
+                .collect();
+            writeln!(f, "{}", row(cells))?;
+        }
+        write!(
+            f,
+            "{}",
+            row((1..=self.stacks.len()).map(|n| n.to_string()).collect())
+        )
+    }
+}
+
+/// What to do with a move of more crates than its source stack holds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Underfilled {
+    /// Move the crates there are and record a warning.
+    #[default]
+    Warn,
+    /// Refuse the move.
+    Error,
+}
+
+/// A move of more crates than its source stack held.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnderfilledMove {
+    /// Position of the move in the journal, counting from 1.
+    pub step: usize,
+    pub stack: usize,
+    pub requested: u32,
+    pub available: usize,
+}
+
+impl fmt::Display for UnderfilledMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {}: {} crates requested from stack {}, which holds {}",
+            self.step,
+            self.requested,
+            self.stack + 1,
+            self.available
+        )
+    }
+}
+
+impl std::error::Error for UnderfilledMove {}
+
 #[derive(Debug)]
-struct MoveInstruction {
+struct JournalEntry {
+    instruction: MoveInstruction,
+    /// The crates taken off the source stack, bottom to top, so the move can
+    /// be undone whatever order the crane put them down in.
+    lifted: Vec<String>,
+    warning: Option<UnderfilledMove>,
+}
+
+/// Applies moves to a [`Cargo`] while recording them, so they can be undone,
+/// redone and replayed.
+pub struct Journal<'a> {
+    cargo: Cargo,
+    crane: &'a dyn Crane,
+    on_underfilled: Underfilled,
+    applied: Vec<JournalEntry>,
+    undone: Vec<MoveInstruction>,
+}
+
+impl<'a> Journal<'a> {
+    pub fn new(cargo: Cargo, crane: &'a dyn Crane, on_underfilled: Underfilled) -> Self {
+        Self {
+            cargo,
+            crane,
+            on_underfilled,
+            applied: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    /// Applies the first `steps` instructions, or all of them if there are
+    /// fewer, to a copy of `cargo`.
+    pub fn replay(
+        cargo: &Cargo,
+        instructions: &[MoveInstruction],
+        steps: usize,
+        crane: &'a dyn Crane,
+        on_underfilled: Underfilled,
+    ) -> Result<Self, UnderfilledMove> {
+        let mut journal = Self::new(cargo.clone(), crane, on_underfilled);
+        for instruction in instructions.iter().take(steps) {
+            journal.apply(instruction)?;
+        }
+        Ok(journal)
+    }
+
+    pub fn cargo(&self) -> &Cargo {
+        &self.cargo
+    }
+
+    /// Number of moves currently applied.
+    pub fn position(&self) -> usize {
+        self.applied.len()
+    }
+
+    /// Warnings for the under-filled moves currently applied.
+    pub fn warnings(&self) -> impl Iterator<Item = &UnderfilledMove> {
+        self.applied
+            .iter()
+            .filter_map(|entry| entry.warning.as_ref())
+    }
+
+    /// Applies a new move, discarding any that were undone.
+    pub fn apply(&mut self, instruction: &MoveInstruction) -> Result<(), UnderfilledMove> {
+        let warning = self.shortfall(instruction);
+        if let (Some(warning), Underfilled::Error) = (&warning, self.on_underfilled) {
+            return Err(warning.clone());
+        }
+        self.undone.clear();
+        self.push(instruction.clone(), warning);
+        Ok(())
+    }
+
+    fn shortfall(&self, instruction: &MoveInstruction) -> Option<UnderfilledMove> {
+        let available = self.cargo.stacks[instruction.source].len();
+        (instruction.amount as usize > available).then(|| UnderfilledMove {
+            step: self.applied.len() + 1,
+            stack: instruction.source,
+            requested: instruction.amount,
+            available,
+        })
+    }
+
+    fn push(&mut self, instruction: MoveInstruction, warning: Option<UnderfilledMove>) {
+        let lifted = self.cargo.lift(&instruction);
+        let mut arranged = lifted.clone();
+        self.crane.arrange(&mut arranged);
+        self.cargo.stacks[instruction.destination].extend(arranged);
+        self.applied.push(JournalEntry {
+            instruction,
+            lifted,
+            warning,
+        });
+    }
+
+    /// Takes back the last applied move. Returns false if there is none.
+    pub fn undo(&mut self) -> bool {
+        let Some(entry) = self.applied.pop() else {
+            return false;
+        };
+        let destination = &mut self.cargo.stacks[entry.instruction.destination];
+        destination.truncate(destination.len() - entry.lifted.len());
+        self.cargo.stacks[entry.instruction.source].extend(entry.lifted);
+        self.undone.push(entry.instruction);
+        true
+    }
+
+    /// Applies the last undone move again. Returns false if there is none.
+    pub fn redo(&mut self) -> bool {
+        let Some(instruction) = self.undone.pop() else {
+            return false;
+        };
+        let warning = self.shortfall(&instruction);
+        self.push(instruction, warning);
+        true
+    }
+
+    /// Undoes or redoes moves until `position` are applied, or as close as
+    /// the journal allows.
+    pub fn seek(&mut self, position: usize) {
+        while self.position() > position && self.undo() {}
+        while self.position() < position && self.redo() {}
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MoveInstruction {
     source: usize,
     destination: usize,
     amount: u32,
 }
 
-impl From<&str> for MoveInstruction {
-    fn from(value: &str) -> Self {
-        let mut word_iter = value.split_ascii_whitespace();
-        word_iter.next();
-        let amount = word_iter.next().unwrap().parse().unwrap();
-        word_iter.next();
-        let source: usize = word_iter.next().unwrap().parse().unwrap();
-        word_iter.next();
-        let destination: usize = word_iter.next().unwrap().parse().unwrap();
+impl<'a> TryFrom<&'a str> for MoveInstruction {
+    type Error = ParseError<'a>;
 
-        Self {
-            source: source - 1,
-            destination: destination - 1,
-            amount,
-        }
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let words: Vec<&str> = value.split_ascii_whitespace().collect();
+        let ["move", amount, "from", source, "to", destination] = words[..] else {
+            return Err(ParseError::new(
+                value,
+                "expected `move <amount> from <stack> to <stack>`",
+            ));
+        };
+
+        Ok(Self {
+            source: parse_stack(source)?,
+            destination: parse_stack(destination)?,
+            amount: parse_number(amount, "a number of crates")?,
+        })
     }
 }
 
+fn parse_stack(stack: &str) -> Result<usize, ParseError<'_>> {
+    parse_number::<usize>(stack, "a stack number")?
+        .checked_sub(1)
+        .ok_or_else(|| ParseError::new(stack, "expected stacks to be numbered from 1"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-    use test::Bencher;
-
-    #[bench]
-    fn bench(b: &mut Bencher) {
-        let raw_input = util::read_input("inputs/day05.txt").unwrap();
-        let (cargo, instructions) = parse(raw_input);
-        b.iter(|| {
-            let mut cargo_ = cargo.clone();
-            instructions
-                .iter()
-                .for_each(|instruction| cargo_.execute_move(instruction))
-        })
+    use crate::days::check_example;
+
+    #[test]
+    fn test_example() {
+        check_example(
+            &Day05,
+            include_str!("examples/day05.txt"),
+            String::from("CMZ"),
+            String::from("MCD"),
+        );
+    }
+
+    #[test]
+    fn test_cranes() {
+        let instruction = MoveInstruction::try_from("move 2 from 1 to 2").unwrap();
+        let cargo = parse_starting_pos("[C]\n[B]\n[A] [D]\n 1   2").unwrap();
+        for (crane, expected) in [
+            (
+                &CrateMover9000 as &dyn Crane,
+                "    [B]\n    [C]\n[A] [D]\n 1   2",
+            ),
+            (&CrateMover9001, "    [C]\n    [B]\n[A] [D]\n 1   2"),
+        ] {
+            let mut cargo = cargo.clone();
+            cargo.execute_move(&instruction, crane);
+            assert_eq!(cargo.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_journal() {
+        let (cargo, instructions) = parse(include_str!("examples/day05.txt")).unwrap();
+        let mut journal = Journal::new(cargo.clone(), &CrateMover9000, Underfilled::Error);
+        for instruction in &instructions {
+            journal.apply(instruction).unwrap();
+        }
+        assert_eq!(journal.cargo().tops(), "CMZ");
+
+        journal.seek(1);
+        let after_one = Journal::replay(
+            &cargo,
+            &instructions,
+            1,
+            &CrateMover9000,
+            Underfilled::Error,
+        );
+        assert_eq!(journal.cargo(), after_one.unwrap().cargo());
+        assert_eq!(
+            journal.cargo().to_string(),
+            "[D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3"
+        );
+
+        assert!(journal.redo());
+        assert!(journal.undo() && journal.undo());
+        assert!(!journal.undo());
+        assert_eq!(journal.cargo(), &cargo);
+
+        journal.seek(instructions.len());
+        assert_eq!(journal.position(), instructions.len());
+        assert_eq!(journal.cargo().tops(), "CMZ");
+    }
+
+    #[test]
+    fn test_journal_underfilled() {
+        let cargo = parse_starting_pos("[A]\n[B] [C]\n 1   2").unwrap();
+        let instruction = MoveInstruction::try_from("move 3 from 1 to 2").unwrap();
+        let underfilled = UnderfilledMove {
+            step: 1,
+            stack: 0,
+            requested: 3,
+            available: 2,
+        };
+
+        let mut journal = Journal::new(cargo.clone(), &CrateMover9001, Underfilled::Error);
+        assert_eq!(journal.apply(&instruction), Err(underfilled.clone()));
+        assert_eq!(journal.cargo(), &cargo);
+
+        let mut journal = Journal::new(cargo.clone(), &CrateMover9001, Underfilled::Warn);
+        journal.apply(&instruction).unwrap();
+        assert_eq!(journal.warnings().collect::<Vec<_>>(), [&underfilled]);
+        assert_eq!(
+            journal.cargo().to_string(),
+            "    [A]\n    [B]\n    [C]\n 1   2"
+        );
+        assert_eq!(journal.cargo().tops(), " A");
+        journal.undo();
+        assert_eq!(journal.cargo(), &cargo);
+        assert_eq!(journal.warnings().count(), 0);
+    }
+
+    #[test]
+    fn test_render() {
+        let (mut cargo, instructions) = parse(include_str!("examples/day05.txt")).unwrap();
+        assert_eq!(
+            cargo.to_string(),
+            "    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3"
+        );
+        for instruction in &instructions {
+            cargo.execute_move(instruction, &CrateMover9000);
+        }
+        assert_eq!(
+            cargo.to_string(),
+            "        [Z]\n        [N]\n        [D]\n[C] [M] [P]\n 1   2   3"
+        );
+    }
+
+    #[test]
+    fn test_render_round_trip() {
+        let mut stacks = vec![Vec::new(); 11];
+        stacks[0] = vec![String::from("A"), String::from("LONG")];
+        stacks[9] = vec![String::from("Z")];
+        stacks[10] = vec![String::from("XY"), String::from("Q"), String::from("R")];
+        let cargo = Cargo::new(stacks);
+        let drawing = cargo.to_string();
+        assert_eq!(
+            drawing.lines().last(),
+            Some("  1      2      3      4      5      6      7      8      9      10     11")
+        );
+        assert_eq!(parse_starting_pos(&drawing), Ok(cargo));
     }
 }
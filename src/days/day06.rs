@@ -1,39 +1,202 @@
-use crate::util;
-use std::collections::HashSet;
+use super::Solution;
+use crate::error::ParseError;
+use std::io::{self, BufReader, Bytes, Read};
 
-pub fn run() {
-    let raw_input = util::read_input("inputs/day06.txt").unwrap();
-    println!("part 1: {}", part_1(&raw_input, 4));
-    println!("part 2: {}", part_1(&raw_input, 14));
+pub struct Day06;
+
+impl Solution for Day06 {
+    type Input = String;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        Ok(input.to_owned())
+    }
+
+    fn part_1(&self, input: &Self::Input) -> u64 {
+        first_marker(input, MarkerKind::Packet)
+    }
+
+    fn part_2(&self, input: &Self::Input) -> u64 {
+        first_marker(input, MarkerKind::Message)
+    }
+}
+
+fn first_marker(input: &str, kind: MarkerKind) -> u64 {
+    let mut detector = MarkerDetector::new(kind.window());
+    input
+        .bytes()
+        .find_map(|byte| detector.push(byte))
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerKind {
+    /// Four different characters in a row.
+    Packet,
+    /// Fourteen different characters in a row.
+    Message,
+}
+
+impl MarkerKind {
+    pub fn window(&self) -> usize {
+        match self {
+            MarkerKind::Packet => 4,
+            MarkerKind::Message => 14,
+        }
+    }
+}
+
+/// Finds every point in a stream of bytes where the last `len` bytes were
+/// all different, using memory that depends only on `len`.
+#[derive(Debug, Clone)]
+pub struct MarkerDetector {
+    /// The last `len` bytes, as a ring buffer.
+    window: Box<[u8]>,
+    counts: [u32; 256],
+    /// How many byte values appear more than once in the window.
+    repeated: usize,
+    seen: u64,
+}
+
+impl MarkerDetector {
+    pub fn new(len: usize) -> Self {
+        assert!(len > 0, "a marker must be at least one byte long");
+        Self {
+            window: vec![0; len].into_boxed_slice(),
+            counts: [0; 256],
+            repeated: 0,
+            seen: 0,
+        }
+    }
+
+    /// Adds the next byte, returning the number of bytes seen so far if they
+    /// end with a marker.
+    pub fn push(&mut self, byte: u8) -> Option<u64> {
+        let len = self.window.len() as u64;
+        let slot = (self.seen % len) as usize;
+        if self.seen >= len {
+            let old = self.window[slot] as usize;
+            self.counts[old] -= 1;
+            if self.counts[old] == 1 {
+                self.repeated -= 1;
+            }
+        }
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 2 {
+            self.repeated += 1;
+        }
+        self.window[slot] = byte;
+        self.seen += 1;
+        (self.seen >= len && self.repeated == 0).then_some(self.seen)
+    }
+}
+
+/// A marker found by [`markers`], ending `offset` bytes into the signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Marker {
+    pub kind: MarkerKind,
+    pub offset: u64,
 }
 
-fn part_1(input: &str, window_size: usize) -> usize {
-    let input_chars: Vec<char> = input.chars().collect();
+/// Every start-of-packet and start-of-message marker in a signal, in order of
+/// where they end. Line breaks are skipped and not counted, so the signal may
+/// be wrapped over several lines.
+pub fn markers<R: Read>(reader: R) -> Markers<R> {
+    Markers {
+        bytes: BufReader::new(reader).bytes(),
+        packet: MarkerDetector::new(MarkerKind::Packet.window()),
+        message: MarkerDetector::new(MarkerKind::Message.window()),
+        pending: None,
+    }
+}
+
+pub struct Markers<R> {
+    bytes: Bytes<BufReader<R>>,
+    packet: MarkerDetector,
+    message: MarkerDetector,
+    /// A message marker ending on the same byte as a packet marker.
+    pending: Option<Marker>,
+}
+
+impl<R: Read> Iterator for Markers<R> {
+    type Item = io::Result<Marker>;
 
-    for (i, window) in input_chars.windows(window_size).enumerate() {
-        let window_char_set: HashSet<char> = HashSet::from_iter(window.iter().copied());
-        if window_char_set.len() == window_size {
-            return i + window_size;
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(marker) = self.pending.take() {
+            return Some(Ok(marker));
+        }
+        loop {
+            let byte = match self.bytes.next()? {
+                Ok(b'\n' | b'\r') => continue,
+                Ok(byte) => byte,
+                Err(err) => return Some(Err(err)),
+            };
+            let packet = self.packet.push(byte).map(|offset| Marker {
+                kind: MarkerKind::Packet,
+                offset,
+            });
+            let message = self.message.push(byte).map(|offset| Marker {
+                kind: MarkerKind::Message,
+                offset,
+            });
+            match (packet, message) {
+                (Some(packet), message) => {
+                    self.pending = message;
+                    return Some(Ok(packet));
+                }
+                (None, Some(message)) => return Some(Ok(message)),
+                (None, None) => {}
+            }
         }
     }
-    0
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-    use test::Bencher;
+    use crate::days::check_example;
+
+    #[test]
+    fn test_example() {
+        check_example(&Day06, include_str!("examples/day06.txt"), 7, 19);
+        for (input, part_1, part_2) in [
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+        ] {
+            check_example(&Day06, input, part_1, part_2);
+        }
+    }
 
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) {
-        let input = util::read_input("inputs/day06.txt").unwrap();
-        b.iter(|| part_1(&input, 4));
+    #[test]
+    fn test_detector_reports_every_marker() {
+        let mut detector = MarkerDetector::new(3);
+        let found: Vec<u64> = "aabcbcda"
+            .bytes()
+            .filter_map(|b| detector.push(b))
+            .collect();
+        assert_eq!(found, [4, 7, 8]);
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) {
-        let input = util::read_input("inputs/day06.txt").unwrap();
-        b.iter(|| part_1(&input, 14));
+    #[test]
+    fn test_markers() {
+        let signal = "mjqjpqmgbljsp\nhdztnvjfqwrcgsmlb\n";
+        let found: Vec<Marker> = markers(signal.as_bytes())
+            .collect::<io::Result<_>>()
+            .unwrap();
+        let offsets = |kind| {
+            found
+                .iter()
+                .filter(|marker| marker.kind == kind)
+                .map(|marker| marker.offset)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(offsets(MarkerKind::Packet)[..3], [7, 8, 9]);
+        assert_eq!(offsets(MarkerKind::Message)[0], 19);
+        assert!(found
+            .windows(2)
+            .all(|pair| pair[0].offset <= pair[1].offset));
     }
 }
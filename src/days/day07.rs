@@ -1,91 +1,638 @@
-use crate::util;
+use super::Solution;
+use crate::answer::Answer;
+use crate::error::{parse_number, ParseError};
 use std::collections::HashMap;
+use std::fmt::{self, Write};
 
 const DEV_SPACE: u64 = 70_000_000;
 const MIN_REQUIRED_SPACE: u64 = 30_000_000;
 
-pub fn run() {
-    let raw_input = util::read_input("inputs/day07.txt").unwrap();
-    println!("part 1: {}", part_1(&raw_input));
-    println!("part 2: {}", part_2(&raw_input));
+pub struct Day07;
+
+impl Solution for Day07 {
+    type Input = FileSystem;
+    type Part1 = u64;
+    type Part2 = Answer;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        FileSystem::try_from(input)
+    }
+
+    fn part_1(&self, input: &Self::Input) -> u64 {
+        part_1(input)
+    }
+
+    /// Unsolved if the files take up more than the whole disk.
+    fn part_2(&self, input: &Self::Input) -> Answer {
+        match part_2(input) {
+            Some(size) => size.into(),
+            None => Answer::Unsolved,
+        }
+    }
 }
 
-fn part_1(input: &str) -> u64 {
-    let dir_size_map = construct_dir_size_map(input);
-    dir_size_map
-        .iter()
-        .filter(|(_, &dir_size)| dir_size <= 100_000)
-        .fold(0, |total_size, (_, &dir_size)| total_size + dir_size)
+fn part_1(fs: &FileSystem) -> u64 {
+    fs.dirs_where(|size| size <= 100_000)
+        .map(|dir| fs.size(dir))
+        .sum()
 }
 
-fn part_2(input: &str) -> u64 {
-    let dir_size_map = construct_dir_size_map(input);
-    dir_size_map
-        .iter()
-        .filter(|(_, &dir_size)| dir_size >= (MIN_REQUIRED_SPACE - (DEV_SPACE - dir_size_map["."])))
-        .map(|(_, &size)| size)
+fn part_2(fs: &FileSystem) -> Option<u64> {
+    let free = DEV_SPACE.checked_sub(fs.total_size())?;
+    let needed = MIN_REQUIRED_SPACE.saturating_sub(free);
+    fs.dirs_where(move |size| size >= needed)
+        .map(|dir| fs.size(dir))
         .min()
-        .unwrap()
-}
-
-fn construct_dir_size_map(input: &str) -> HashMap<String, u64> {
-    let mut cwd = String::new();
-    let mut dir_size_map: HashMap<String, u64> = HashMap::new();
-    let input_without_ls_or_dir = input
-        .lines()
-        .filter(|&line| line != "$ ls" && &line[0..3] != "dir");
-    for line in input_without_ls_or_dir {
-        match line {
-            // use . as the root dir so that / can easily be used as a delimiter
-            "$ cd /" => cwd.push('.'),
-            "$ cd .." => cwd.truncate(cwd.rfind('/').unwrap()),
-            _ if line.starts_with("$ cd") => {
-                cwd.push('/');
-                cwd.push_str(&line[5..]);
-            }
+}
+
+/// Index of a file or directory in a [`FileSystem`].
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    File,
+    Dir { children: Vec<NodeId> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub name: String,
+    pub parent: Option<NodeId>,
+    /// A file's size, or the total size of everything in a directory.
+    pub size: u64,
+    pub kind: NodeKind,
+}
+
+impl Node {
+    pub fn is_dir(&self) -> bool {
+        matches!(self.kind, NodeKind::Dir { .. })
+    }
+}
+
+/// The directory tree seen in a terminal log, rooted at `/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
+}
+
+impl FileSystem {
+    pub const ROOT: NodeId = 0;
+
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: String::from("/"),
+                parent: None,
+                size: 0,
+                kind: NodeKind::Dir {
+                    children: Vec::new(),
+                },
+            }],
+        }
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn size(&self, id: NodeId) -> u64 {
+        self.nodes[id].size
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.size(Self::ROOT)
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        match &self.nodes[id].kind {
+            NodeKind::Dir { children } => children,
+            NodeKind::File => &[],
+        }
+    }
+
+    pub fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        self.children(dir)
+            .iter()
+            .copied()
+            .find(|&child| self.nodes[child].name == name)
+    }
+
+    /// Every directory, starting with the root.
+    pub fn dirs(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.nodes.len()).filter(|&id| self.nodes[id].is_dir())
+    }
+
+    /// Directories whose total size satisfies `pred`.
+    pub fn dirs_where<'a>(
+        &'a self,
+        pred: impl Fn(u64) -> bool + 'a,
+    ) -> impl Iterator<Item = NodeId> + 'a {
+        self.dirs().filter(move |&dir| pred(self.size(dir)))
+    }
+
+    /// The `n` largest directories, largest first.
+    pub fn largest_dirs(&self, n: usize) -> Vec<NodeId> {
+        let mut dirs: Vec<NodeId> = self.dirs().collect();
+        dirs.sort_by_key(|&dir| std::cmp::Reverse(self.size(dir)));
+        dirs.truncate(n);
+        dirs
+    }
+
+    /// Finds a file or directory by an absolute path such as `/a/e`.
+    pub fn lookup(&self, path: &str) -> Option<NodeId> {
+        path.strip_prefix('/')?
+            .split('/')
+            .filter(|name| !name.is_empty())
+            .try_fold(Self::ROOT, |dir, name| self.child(dir, name))
+    }
+
+    /// The absolute path of a file or directory.
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names = Vec::new();
+        let mut node = id;
+        while let Some(parent) = self.nodes[node].parent {
+            names.push(self.nodes[node].name.as_str());
+            node = parent;
+        }
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    /// Adds an empty directory to `parent`, or returns the one already there.
+    /// Returns `None` if `parent` already has a file with that name.
+    pub fn add_dir(&mut self, parent: NodeId, name: &str) -> Option<NodeId> {
+        if let Some(existing) = self.child(parent, name) {
+            return self.nodes[existing].is_dir().then_some(existing);
+        }
+        Some(self.push(
+            parent,
+            name,
+            0,
+            NodeKind::Dir {
+                children: Vec::new(),
+            },
+        ))
+    }
+
+    /// Adds a file to `parent`, counting its size towards every directory
+    /// above it. A file that is already there is left as it is.
+    pub fn add_file(&mut self, parent: NodeId, name: &str, size: u64) -> NodeId {
+        if let Some(existing) = self.child(parent, name) {
+            return existing;
+        }
+        let id = self.push(parent, name, size, NodeKind::File);
+        let mut dir = Some(parent);
+        while let Some(id) = dir {
+            self.nodes[id].size += size;
+            dir = self.nodes[id].parent;
+        }
+        id
+    }
+
+    fn push(&mut self, parent: NodeId, name: &str, size: u64, kind: NodeKind) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.to_owned(),
+            parent: Some(parent),
+            size,
+            kind,
+        });
+        if let NodeKind::Dir { children } = &mut self.nodes[parent].kind {
+            children.push(id);
+        }
+        id
+    }
+}
+
+impl FileSystem {
+    /// An indented listing of every file and directory with its size,
+    /// children sorted by name:
+    ///
+    /// ```text
+    /// - / (dir, size=48381165)
+    ///   - a (dir, size=94853)
+    ///     - e (dir, size=584)
+    ///       - i (file, size=584)
+    /// ```
+    pub fn render_tree(&self) -> String {
+        let mut out = String::new();
+        self.write_tree(&mut out, Self::ROOT, 0);
+        out
+    }
+
+    fn write_tree(&self, out: &mut String, id: NodeId, depth: usize) {
+        let node = self.node(id);
+        let kind = if node.is_dir() { "dir" } else { "file" };
+        writeln!(
+            out,
+            "{:indent$}- {} ({}, size={})",
+            "",
+            node.name,
+            kind,
+            node.size,
+            indent = depth * 2
+        )
+        .unwrap();
+        let mut children = self.children(id).to_vec();
+        children.sort_by(|&a, &b| self.node(a).name.cmp(&self.node(b).name));
+        for child in children {
+            self.write_tree(out, child, depth + 1);
+        }
+    }
+
+    /// Every directory with its total size in the style of `du -h`, largest
+    /// first.
+    pub fn render_du(&self) -> String {
+        let mut dirs: Vec<NodeId> = self.dirs().collect();
+        dirs.sort_by_key(|&dir| (std::cmp::Reverse(self.size(dir)), self.path(dir)));
+        let mut out = String::new();
+        for dir in dirs {
+            writeln!(out, "{:<6}{}", human_size(self.size(dir)), self.path(dir)).unwrap();
+        }
+        out
+    }
+}
+
+/// Formats a size in bytes with a binary unit suffix as `du -h` does,
+/// rounding up, e.g. `584`, `9.5K` or `46M`.
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["K", "M", "G", "T", "P", "E"];
+    if bytes < 1024 {
+        return bytes.to_string();
+    }
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    size /= 1024.0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if size < 10.0 {
+        let tenths = (size * 10.0).ceil() / 10.0;
+        if tenths < 10.0 {
+            return format!("{:.1}{}", tenths, UNITS[unit]);
+        }
+    }
+    format!("{}{}", size.ceil(), UNITS[unit])
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One line of the terminal log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogLine<'a> {
+    CdRoot,
+    CdUp,
+    Cd(&'a str),
+    Ls,
+    Dir(&'a str),
+    File(u64, &'a str),
+}
+
+impl<'a> TryFrom<&'a str> for LogLine<'a> {
+    type Error = ParseError<'a>;
+
+    fn try_from(line: &'a str) -> Result<Self, Self::Error> {
+        Ok(match line {
+            "$ cd /" => LogLine::CdRoot,
+            "$ cd .." => LogLine::CdUp,
+            "$ ls" => LogLine::Ls,
+            _ if line.starts_with('$') => match line.strip_prefix("$ cd ") {
+                Some(name) if !name.is_empty() => LogLine::Cd(name),
+                _ => return Err(ParseError::new(line, "expected `cd <dir>` or `ls`")),
+            },
             _ => {
-                let file_size: u64 = line
-                    .split_ascii_whitespace()
-                    .next()
-                    .unwrap()
-                    .parse()
-                    .unwrap();
-                update_size_of_cwd_and_parents(&mut dir_size_map, &cwd, file_size)
+                let (size, name) = line
+                    .split_once(' ')
+                    .filter(|(_, name)| !name.is_empty())
+                    .ok_or_else(|| {
+                        ParseError::new(line, "expected `dir <name>` or `<size> <file name>`")
+                    })?;
+                if size == "dir" {
+                    LogLine::Dir(name)
+                } else {
+                    LogLine::File(parse_number(size, "a file size")?, name)
+                }
+            }
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a str> for FileSystem {
+    type Error = ParseError<'a>;
+
+    /// Builds the tree from a log of `cd` and `ls` commands and their output.
+    /// See [`validate`] for logs that may not be consistent.
+    fn try_from(input: &'a str) -> Result<Self, Self::Error> {
+        let mut fs = FileSystem::new();
+        let mut cwd = FileSystem::ROOT;
+        for line in input.lines() {
+            match LogLine::try_from(line)? {
+                LogLine::CdRoot => cwd = FileSystem::ROOT,
+                LogLine::CdUp => {
+                    cwd = fs
+                        .node(cwd)
+                        .parent
+                        .ok_or_else(|| ParseError::new(line, "cannot leave the root directory"))?
+                }
+                LogLine::Cd(name) => {
+                    cwd = fs
+                        .add_dir(cwd, name)
+                        .ok_or_else(|| ParseError::new(line, "cannot cd into a file"))?
+                }
+                LogLine::Ls => {}
+                LogLine::Dir(name) => {
+                    fs.add_dir(cwd, name);
+                }
+                LogLine::File(size, name) => {
+                    fs.add_file(cwd, name, size);
+                }
             }
         }
+        Ok(fs)
     }
+}
 
-    dir_size_map
+/// Something wrong with a terminal log, found by [`validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// A line that is not a command or `ls` output.
+    Malformed(String),
+    /// `ls` in a directory that was already listed.
+    RepeatedListing { dir: String, first_line: usize },
+    /// `cd` into a directory that the listing of the current directory did
+    /// not show, or when the current directory was never listed.
+    UnlistedDir { dir: String },
+    /// `cd` into a file.
+    CdIntoFile { file: String },
+    /// `cd ..` in the root directory.
+    CdPastRoot,
+    /// `ls` output that does not follow an `ls`.
+    OutputWithoutLs,
 }
 
-fn update_size_of_cwd_and_parents(
-    dir_size_map: &mut HashMap<String, u64>,
-    path: &str,
-    file_size: u64,
-) {
-    let dir_size = dir_size_map.entry(path.to_string()).or_insert(0);
-    *dir_size += file_size;
-    path.match_indices('/').for_each(|(i, _)| {
-        let dir_size = dir_size_map.entry(path[0..i].to_string()).or_insert(0);
-        *dir_size += file_size;
-    })
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Malformed(reason) => write!(f, "{}", reason),
+            Problem::RepeatedListing { dir, first_line } => write!(
+                f,
+                "{} is listed again, it was first listed on line {}",
+                dir, first_line
+            ),
+            Problem::UnlistedDir { dir } => {
+                write!(f, "cd into {}, which was not in any listing", dir)
+            }
+            Problem::CdIntoFile { file } => write!(f, "cd into {}, which is a file", file),
+            Problem::CdPastRoot => write!(f, "cd .. in the root directory"),
+            Problem::OutputWithoutLs => write!(f, "output without an `ls`"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Line number in the log, counting from 1.
+    pub line: usize,
+    pub problem: Problem,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.problem)
+    }
+}
+
+/// What the output lines after a command belong to, for [`validate`].
+#[derive(Debug, PartialEq, Eq)]
+enum Output {
+    /// No `ls` came before them.
+    None,
+    /// The first listing of a directory.
+    Listing,
+    /// A listing of a directory already listed.
+    RepeatedListing,
+}
+
+/// Checks a terminal log for lines that do not parse or do not agree with
+/// what came before, carrying on past each problem to report them all.
+pub fn validate(input: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut fs = FileSystem::new();
+    let mut cwd = FileSystem::ROOT;
+    // The line each directory was listed on.
+    let mut listed: HashMap<NodeId, usize> = HashMap::new();
+    // What the lines since the last command are output of.
+    let mut output = Output::None;
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let mut report = |problem| {
+            diagnostics.push(Diagnostic {
+                line: line_number,
+                problem,
+            })
+        };
+        let log_line = match LogLine::try_from(line) {
+            Ok(log_line) => log_line,
+            Err(err) => {
+                report(Problem::Malformed(err.reason().to_owned()));
+                continue;
+            }
+        };
+        match log_line {
+            LogLine::Dir(_) | LogLine::File(..) if output == Output::None => {
+                report(Problem::OutputWithoutLs)
+            }
+            // Already reported along with the `ls` itself.
+            LogLine::Dir(_) | LogLine::File(..) if output == Output::RepeatedListing => {}
+            LogLine::Dir(name) => {
+                fs.add_dir(cwd, name);
+            }
+            LogLine::File(size, name) => {
+                fs.add_file(cwd, name, size);
+            }
+            LogLine::CdRoot => {
+                output = Output::None;
+                cwd = FileSystem::ROOT;
+            }
+            LogLine::CdUp => {
+                output = Output::None;
+                match fs.node(cwd).parent {
+                    Some(parent) => cwd = parent,
+                    None => report(Problem::CdPastRoot),
+                }
+            }
+            LogLine::Cd(name) => {
+                output = Output::None;
+                cwd = match fs.child(cwd, name) {
+                    Some(file) if !fs.node(file).is_dir() => {
+                        report(Problem::CdIntoFile {
+                            file: fs.path(file),
+                        });
+                        cwd
+                    }
+                    Some(dir) if listed.contains_key(&cwd) => dir,
+                    _ => match fs.add_dir(cwd, name) {
+                        Some(dir) => {
+                            report(Problem::UnlistedDir { dir: fs.path(dir) });
+                            dir
+                        }
+                        None => cwd,
+                    },
+                };
+            }
+            LogLine::Ls => match listed.get(&cwd) {
+                Some(&first_line) => {
+                    output = Output::RepeatedListing;
+                    report(Problem::RepeatedListing {
+                        dir: fs.path(cwd),
+                        first_line,
+                    });
+                }
+                None => {
+                    listed.insert(cwd, line_number);
+                    output = Output::Listing;
+                }
+            },
+        }
+    }
+    diagnostics
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-    use test::Bencher;
+    use crate::days::check_example;
+
+    #[test]
+    fn test_example() {
+        check_example(&Day07, include_str!("examples/day07.txt"), 95437, 24933642);
+    }
 
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) {
-        let input = util::read_input("inputs/day07.txt").unwrap();
-        b.iter(|| part_1(&input));
+    #[test]
+    fn test_queries() {
+        let fs = FileSystem::try_from(include_str!("examples/day07.txt")).unwrap();
+        assert_eq!(fs.total_size(), 48381165);
+
+        let e = fs.lookup("/a/e").unwrap();
+        assert_eq!((fs.size(e), fs.path(e)), (584, String::from("/a/e")));
+        assert_eq!(fs.size(fs.lookup("/d/d.log").unwrap()), 8033020);
+        assert_eq!(fs.lookup("/"), Some(FileSystem::ROOT));
+        assert_eq!(fs.lookup("/a/x"), None);
+
+        let names =
+            |ids: Vec<NodeId>| -> Vec<String> { ids.into_iter().map(|id| fs.path(id)).collect() };
+        assert_eq!(names(fs.largest_dirs(2)), ["/", "/d"]);
+        assert_eq!(
+            names(fs.dirs_where(|size| size <= 100_000).collect()),
+            ["/a", "/a/e"]
+        );
+    }
+
+    #[test]
+    fn test_repeated_listing() {
+        let fs = FileSystem::try_from("$ cd /\n$ ls\n10 a\n$ ls\n10 a\n").unwrap();
+        assert_eq!(fs.total_size(), 10);
+    }
+
+    #[test]
+    fn test_over_capacity() {
+        let fs = FileSystem::try_from("$ cd /\n$ ls\n70000001 a\n").unwrap();
+        assert_eq!(Day07.part_2(&fs), Answer::Unsolved);
+    }
+
+    #[test]
+    fn test_cd_into_file() {
+        let input = "$ cd /\n$ ls\n10 a\n$ cd a\n$ ls\n5 b\n";
+        let err = FileSystem::try_from(input).unwrap_err();
+        assert_eq!(err.reason(), "cannot cd into a file");
+        let err = err.locate(7, input).to_string();
+        assert!(err.starts_with("day 07, line 4, column 1:"));
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) {
-        let input = util::read_input("inputs/day07.txt").unwrap();
-        b.iter(|| part_2(&input));
+    #[test]
+    fn test_render_tree() {
+        let fs = FileSystem::try_from(include_str!("examples/day07.txt")).unwrap();
+        let tree = fs.render_tree();
+        let lines: Vec<&str> = tree.lines().collect();
+        assert_eq!(lines.len(), 14);
+        assert_eq!(
+            lines[..5],
+            [
+                "- / (dir, size=48381165)",
+                "  - a (dir, size=94853)",
+                "    - e (dir, size=584)",
+                "      - i (file, size=584)",
+                "    - f (file, size=29116)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_du() {
+        let fs = FileSystem::try_from(include_str!("examples/day07.txt")).unwrap();
+        assert_eq!(fs.render_du(), "47M   /\n24M   /d\n93K   /a\n584   /a/e\n");
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(0), "0");
+        assert_eq!(human_size(1023), "1023");
+        assert_eq!(human_size(1024), "1.0K");
+        assert_eq!(human_size(9728), "9.5K");
+        assert_eq!(human_size(10_239), "10K");
+        assert_eq!(human_size(94853), "93K");
+        assert_eq!(human_size(48381165), "47M");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(validate(include_str!("examples/day07.txt")), []);
+
+        let log = "$ cd /\n$ ls\ndir a\n10 b\n$ ls\n10 b\n$ cd c\n$ cd ..\n$ cd ..\n5\n$ rm b\n$ cd /\n10 x\n$ cd b\n";
+        let problems: Vec<(usize, Problem)> = validate(log)
+            .into_iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.problem))
+            .collect();
+        assert_eq!(
+            problems,
+            [
+                (
+                    5,
+                    Problem::RepeatedListing {
+                        dir: String::from("/"),
+                        first_line: 2
+                    }
+                ),
+                (
+                    7,
+                    Problem::UnlistedDir {
+                        dir: String::from("/c")
+                    }
+                ),
+                (9, Problem::CdPastRoot),
+                (
+                    10,
+                    Problem::Malformed(String::from(
+                        "expected `dir <name>` or `<size> <file name>`"
+                    ))
+                ),
+                (
+                    11,
+                    Problem::Malformed(String::from("expected `cd <dir>` or `ls`"))
+                ),
+                (13, Problem::OutputWithoutLs),
+                (
+                    14,
+                    Problem::CdIntoFile {
+                        file: String::from("/b")
+                    }
+                ),
+            ]
+        );
     }
 }
@@ -1,25 +1,205 @@
-use crate::util;
+use super::Solution;
+use crate::error::ParseError;
+#[cfg(test)]
 use itertools::iproduct;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    type Input = Vec<Vec<i32>>;
+    type Part1 = usize;
+    type Part2 = u64;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        process(input)
+    }
 
-pub fn run() {
-    let raw_input = util::read_input("inputs/day08.txt").unwrap();
-    let trees = process(raw_input);
-    println!("part 1: {}", part_1(trees.clone()));
-    println!("part 2: {}", part_2(trees));
+    fn part_1(&self, input: &Self::Input) -> usize {
+        visibility(input)
+            .iter()
+            .flatten()
+            .filter(|&&visible| visible)
+            .count()
+    }
+
+    fn part_2(&self, input: &Self::Input) -> u64 {
+        scenic_scores(input).into_iter().flatten().max().unwrap()
+    }
 }
 
-fn process(input: String) -> Vec<Vec<i32>> {
-    input
+pub fn process(input: &str) -> Result<Vec<Vec<i32>>, ParseError<'_>> {
+    let trees: Vec<Vec<i32>> = input
         .lines()
         .map(|line| {
-            line.chars()
-                .map(|height| height.to_string().parse().unwrap())
+            line.char_indices()
+                .map(|(i, height)| {
+                    height.to_digit(10).map(|h| h as i32).ok_or_else(|| {
+                        ParseError::new(&line[i..i + height.len_utf8()], "expected a tree height")
+                    })
+                })
                 .collect()
         })
-        .collect()
+        .collect::<Result<_, _>>()?;
+
+    let width = trees.first().map_or(0, |row| row.len());
+    if width == 0 {
+        return Err(ParseError::new(input, "expected a grid of trees"));
+    }
+    for (line, row) in input.lines().zip(&trees) {
+        if row.len() != width {
+            return Err(ParseError::new(
+                line,
+                format!("expected a row of {} trees", width),
+            ));
+        }
+    }
+    Ok(trees)
+}
+
+/// Every row and column of a `height` by `width` grid, forwards and
+/// backwards, as the positions passed on the way along.
+fn lines(height: usize, width: usize) -> impl Iterator<Item = Vec<(usize, usize)>> {
+    let rows = (0..height).map(move |row| (0..width).map(|col| (row, col)).collect());
+    let cols = (0..width).map(move |col| (0..height).map(|row| (row, col)).collect());
+    rows.chain(cols).flat_map(|line: Vec<_>| {
+        let reversed = line.iter().rev().copied().collect();
+        [line, reversed]
+    })
 }
 
-fn part_1(trees: Vec<Vec<i32>>) -> usize {
+/// Which trees can be seen from outside the grid, found with one sweep along
+/// each row and column in each direction.
+pub fn visibility(trees: &[Vec<i32>]) -> Vec<Vec<bool>> {
+    let (height, width) = (trees.len(), trees[0].len());
+    let mut visible = vec![vec![false; width]; height];
+    for line in lines(height, width) {
+        let mut tallest = -1;
+        for (row, col) in line {
+            if trees[row][col] > tallest {
+                visible[row][col] = true;
+                tallest = trees[row][col];
+            }
+        }
+    }
+    visible
+}
+
+/// The scenic score of every tree. Each sweep keeps a stack of the trees
+/// behind that are taller than everything since, so the tree blocking the
+/// view is found in amortized constant time.
+pub fn scenic_scores(trees: &[Vec<i32>]) -> Vec<Vec<u64>> {
+    let (height, width) = (trees.len(), trees[0].len());
+    let mut scores = vec![vec![1; width]; height];
+    let mut stack: Vec<usize> = Vec::new();
+    for line in lines(height, width) {
+        stack.clear();
+        for (i, &(row, col)) in line.iter().enumerate() {
+            let tree = trees[row][col];
+            while let Some(&behind) = stack.last() {
+                let (behind_row, behind_col) = line[behind];
+                if trees[behind_row][behind_col] >= tree {
+                    break;
+                }
+                stack.pop();
+            }
+            let distance = stack.last().map_or(i, |&blocker| i - blocker);
+            scores[row][col] *= distance as u64;
+            stack.push(i);
+        }
+    }
+    scores
+}
+
+/// A grid of values to draw, such as the [`visibility`] mask or the
+/// [`scenic_scores`]. Brightness is on a log scale, since a few spots score
+/// orders of magnitude above the rest.
+pub struct Heatmap {
+    values: Vec<Vec<u64>>,
+    max: u64,
+}
+
+impl Heatmap {
+    pub fn new(values: Vec<Vec<u64>>) -> Self {
+        let max = values.iter().flatten().copied().max().unwrap_or(0);
+        Self { values, max }
+    }
+
+    pub fn visibility(trees: &[Vec<i32>]) -> Self {
+        let values = visibility(trees)
+            .into_iter()
+            .map(|row| row.into_iter().map(u64::from).collect())
+            .collect();
+        Self::new(values)
+    }
+
+    pub fn scenic_scores(trees: &[Vec<i32>]) -> Self {
+        Self::new(scenic_scores(trees))
+    }
+
+    /// How bright `value` is drawn, from 0 to 255.
+    fn level(&self, value: u64) -> u8 {
+        if self.max == 0 {
+            return 0;
+        }
+        let scale = (value as f64).ln_1p() / (self.max as f64).ln_1p();
+        (scale * 255.0).round() as u8
+    }
+
+    /// The colour for `value`, running from black through red and yellow to
+    /// white.
+    fn color(&self, value: u64) -> [u8; 3] {
+        let level = self.level(value) as u32 * 3;
+        let channel = |offset: u32| level.saturating_sub(offset).min(255) as u8;
+        [channel(0), channel(255), channel(510)]
+    }
+
+    /// The heatmap as rows of ANSI true-colour blocks, two columns per tree
+    /// so that they come out roughly square.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for row in &self.values {
+            for &value in row {
+                let [r, g, b] = self.color(value);
+                write!(out, "\x1b[48;2;{};{};{}m  ", r, g, b).unwrap();
+            }
+            out.push_str("\x1b[0m\n");
+        }
+        out
+    }
+
+    /// Writes the heatmap as a binary greyscale PGM image.
+    pub fn write_pgm(&self, out: &mut impl Write) -> io::Result<()> {
+        self.write_netpbm(out, "P5", |value| vec![self.level(value)])
+    }
+
+    /// Writes the heatmap as a binary PPM image in the terminal colours.
+    pub fn write_ppm(&self, out: &mut impl Write) -> io::Result<()> {
+        self.write_netpbm(out, "P6", |value| self.color(value).to_vec())
+    }
+
+    fn write_netpbm(
+        &self,
+        out: &mut impl Write,
+        magic: &str,
+        pixel: impl Fn(u64) -> Vec<u8>,
+    ) -> io::Result<()> {
+        let width = self.values.first().map_or(0, |row| row.len());
+        writeln!(out, "{}\n{} {}\n255", magic, width, self.values.len())?;
+        let pixels: Vec<u8> = self
+            .values
+            .iter()
+            .flatten()
+            .flat_map(|&v| pixel(v))
+            .collect();
+        out.write_all(&pixels)
+    }
+}
+
+/// Straightforward O(n³) version of [`visibility`], kept to check it against.
+#[cfg(test)]
+fn visibility_reference(trees: &[Vec<i32>]) -> Vec<Vec<bool>> {
     let (height, width) = (trees.len(), trees[0].len());
     let mut visible_trees = vec![vec![false; width]; height];
 
@@ -57,13 +237,11 @@ fn part_1(trees: Vec<Vec<i32>>) -> usize {
         }
     }
     visible_trees
-        .iter()
-        .flat_map(|row| row.iter())
-        .filter(|&is_visible| *is_visible)
-        .count()
 }
 
-fn part_2(trees: Vec<Vec<i32>>) -> i32 {
+/// Straightforward version of [`scenic_scores`], kept to check it against.
+#[cfg(test)]
+fn scenic_scores_reference(trees: &[Vec<i32>]) -> Vec<Vec<u64>> {
     let (height, width) = (trees.len(), trees[0].len());
     let mut scenic_scores = vec![vec![1; width]; height];
     for (row, col) in iproduct!(0..height, 0..width) {
@@ -106,34 +284,86 @@ fn part_2(trees: Vec<Vec<i32>>) -> i32 {
     }
 
     scenic_scores
-        .iter()
-        .flat_map(|row| row.iter())
-        .max()
-        .cloned()
-        .unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-    use test::Bencher;
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) {
-        let input = util::read_input("inputs/day08.txt").unwrap();
-        b.iter(|| {
-            let trees = process(input.clone());
-            part_1(trees);
-        })
+    use crate::days::check_example;
+
+    #[test]
+    fn test_example() {
+        check_example(&Day08, include_str!("examples/day08.txt"), 21, 8);
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) {
-        let input = util::read_input("inputs/day08.txt").unwrap();
-        b.iter(|| {
-            let trees = process(input.clone());
-            part_2(trees);
-        })
+    /// A forest of pseudo-random heights, skewed towards tall trees so that
+    /// views are blocked at a range of distances.
+    fn forest(height: usize, width: usize, seed: u64) -> Vec<Vec<i32>> {
+        let mut state = seed;
+        (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        let roll = (state >> 33) % 100;
+                        (roll as f64).sqrt() as i32
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sweeps_match_reference() {
+        for (seed, (height, width)) in [(1, 1), (1, 9), (9, 1), (5, 5), (13, 9), (40, 40)]
+            .into_iter()
+            .enumerate()
+        {
+            let trees = forest(height, width, seed as u64);
+            assert_eq!(
+                visibility(&trees),
+                visibility_reference(&trees),
+                "{}x{}",
+                height,
+                width
+            );
+            assert_eq!(
+                scenic_scores(&trees),
+                scenic_scores_reference(&trees),
+                "{}x{}",
+                height,
+                width
+            );
+        }
+    }
+
+    #[test]
+    fn test_scenic_scores() {
+        let trees = process(include_str!("examples/day08.txt")).unwrap();
+        let scores = scenic_scores(&trees);
+        assert_eq!((scores[1][2], scores[3][2]), (4, 8));
+        assert_eq!(scores[0], [0; 5]);
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let heatmap = Heatmap::new(vec![vec![0, 1], vec![3, 15]]);
+        assert_eq!(heatmap.color(0), [0, 0, 0]);
+        assert_eq!(heatmap.color(15), [255, 255, 255]);
+        assert_eq!(heatmap.level(3), 128);
+
+        let mut pgm = Vec::new();
+        heatmap.write_pgm(&mut pgm).unwrap();
+        assert_eq!(pgm, b"P5\n2 2\n255\n\x00\x40\x80\xff");
+
+        let mut ppm = Vec::new();
+        heatmap.write_ppm(&mut ppm).unwrap();
+        assert_eq!(ppm.len(), "P6\n2 2\n255\n".len() + 12);
+
+        let rendered = heatmap.render();
+        assert_eq!(rendered.lines().count(), 2);
+        assert!(rendered.starts_with("\x1b[48;2;0;0;0m  \x1b[48;2;192;0;0m  \x1b[0m\n"));
     }
 }
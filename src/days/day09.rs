@@ -1,46 +1,233 @@
-use crate::util;
-use std::cell::Cell;
+use super::Solution;
+use crate::error::{parse_number, ParseError};
 use std::collections::HashSet;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
 
-const UP: Pos = Pos { x: 1, y: 0 };
-const RIGHT: Pos = Pos { x: 0, y: 1 };
-const DOWN: Pos = Pos { x: -1, y: 0 };
-const LEFT: Pos = Pos { x: 0, y: -1 };
+const UP: Pos = Pos { x: 1, y: 0, z: 0 };
+const RIGHT: Pos = Pos { x: 0, y: 1, z: 0 };
+const DOWN: Pos = Pos { x: -1, y: 0, z: 0 };
+const LEFT: Pos = Pos { x: 0, y: -1, z: 0 };
+const FORWARD: Pos = Pos { x: 0, y: 0, z: 1 };
+const BACK: Pos = Pos { x: 0, y: 0, z: -1 };
+const START: Pos = Pos { x: 0, y: 0, z: 0 };
 
-pub fn run() {
-    let raw_input = util::read_input("inputs/day09.txt").unwrap();
-    let input = parse(raw_input);
-    let rope = Rope::new(2);
-    println!("part 1: {}", part_1(&input, rope));
-    let rope = Rope::new(10);
-    println!("part 2: {}", part_1(&input, rope));
+pub struct Day09;
+
+impl Solution for Day09 {
+    type Input = Vec<(Pos, usize)>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        parse(input)
+    }
+
+    fn part_1(&self, input: &Self::Input) -> usize {
+        part_1(input, Rope::new(2))
+    }
+
+    fn part_2(&self, input: &Self::Input) -> usize {
+        part_1(input, Rope::new(10))
+    }
+}
+
+pub fn parse(input: &str) -> Result<Vec<(Pos, usize)>, ParseError<'_>> {
+    parse_moves(input, Dimensions::Two)
 }
 
-fn parse(input: String) -> Vec<(Pos, usize)> {
+/// Parses moves that may be diagonal, or in 3D leave the plane.
+pub fn parse_moves(
+    input: &str,
+    dimensions: Dimensions,
+) -> Result<Vec<(Pos, usize)>, ParseError<'_>> {
     input
         .lines()
         .map(|line| {
-            let (pos_str, amt_str) = line.split_once(' ').unwrap();
-            (pos_str.into(), amt_str.parse().unwrap())
+            let (pos_str, amt_str) = line
+                .split_once(' ')
+                .ok_or_else(|| ParseError::new(line, "expected `<direction> <steps>`"))?;
+            Ok((
+                parse_direction(pos_str, dimensions)?,
+                parse_number(amt_str, "a number of steps")?,
+            ))
         })
         .collect()
 }
 
-fn part_1(moves: &[(Pos, usize)], rope: Rope) -> usize {
+fn part_1(moves: &[(Pos, usize)], mut rope: Rope) -> usize {
     let mut visited: HashSet<Pos> = HashSet::new();
     for (direction, amt) in moves {
         for _ in 0..*amt {
             rope.step(direction);
-            visited.insert(rope.knots[rope.knots.len() - 1].get());
+            visited.insert(*rope.knots().last().unwrap());
         }
     }
     visited.len()
 }
 
+/// The knot positions of a rope after every step of a series of moves,
+/// starting with all knots at the origin.
+#[derive(Debug, Clone)]
+pub struct Trace {
+    frames: Vec<Vec<Pos>>,
+}
+
+impl Trace {
+    pub fn record(moves: &[(Pos, usize)], mut rope: Rope) -> Self {
+        let mut frames = vec![rope.knots.clone()];
+        for (direction, amt) in moves {
+            for _ in 0..*amt {
+                rope.step(direction);
+                frames.push(rope.knots.clone());
+            }
+        }
+        Self { frames }
+    }
+
+    /// The knots at each step, head first.
+    pub fn frames(&self) -> &[Vec<Pos>] {
+        &self.frames
+    }
+
+    /// Every position the tail has been in.
+    pub fn visited(&self) -> HashSet<Pos> {
+        self.frames
+            .iter()
+            .filter_map(|knots| knots.last().copied())
+            .collect()
+    }
+
+    /// The smallest viewport holding every knot at every step.
+    pub fn bounds(&self) -> Viewport {
+        Viewport::fit(self.frames.iter().flatten())
+    }
+
+    /// Draws each step over the last at `fps` frames per second. With a
+    /// `size` of rows and columns the view follows the head, otherwise it is
+    /// fixed at the [`bounds`](Trace::bounds) of the whole trace.
+    pub fn animate(
+        &self,
+        out: &mut impl Write,
+        fps: f64,
+        size: Option<(usize, usize)>,
+    ) -> io::Result<()> {
+        let delay = Duration::from_secs_f64(1.0 / fps);
+        let bounds = self.bounds();
+        for (step, knots) in self.frames.iter().enumerate() {
+            let viewport = size.map_or(bounds, |(rows, cols)| {
+                Viewport::centered(knots[0], rows, cols)
+            });
+            write!(out, "\x1b[H\x1b[2J{}", render_knots(knots, &viewport))?;
+            writeln!(out, "step {}/{}", step, self.frames.len() - 1)?;
+            out.flush()?;
+            thread::sleep(delay);
+        }
+        Ok(())
+    }
+}
+
+/// The part of the plane to draw, as inclusive bounds. Rows run along `x`
+/// with up at the top, and columns along `y`. Ropes in 3D are drawn as seen
+/// from above, flattened onto the plane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub min: Pos,
+    pub max: Pos,
+}
+
+impl Viewport {
+    /// The smallest viewport holding all of `positions` and the start.
+    pub fn fit<'a>(positions: impl IntoIterator<Item = &'a Pos>) -> Self {
+        positions.into_iter().fold(
+            Self {
+                min: START,
+                max: START,
+            },
+            |view, pos| Self {
+                min: Pos {
+                    x: view.min.x.min(pos.x),
+                    y: view.min.y.min(pos.y),
+                    z: 0,
+                },
+                max: Pos {
+                    x: view.max.x.max(pos.x),
+                    y: view.max.y.max(pos.y),
+                    z: 0,
+                },
+            },
+        )
+    }
+
+    /// A viewport of `rows` by `cols` with `center` in the middle.
+    pub fn centered(center: Pos, rows: usize, cols: usize) -> Self {
+        let (rows, cols) = (rows.max(1) as isize, cols.max(1) as isize);
+        let min = Pos {
+            x: center.x - (rows - 1) / 2,
+            y: center.y - (cols - 1) / 2,
+            z: 0,
+        };
+        let max = Pos {
+            x: min.x + rows - 1,
+            y: min.y + cols - 1,
+            z: 0,
+        };
+        Self { min, max }
+    }
+
+    /// Draws the viewport a row at a time from the top, with `cell` giving
+    /// the character at each position.
+    fn render(&self, cell: impl Fn(Pos) -> char) -> String {
+        let mut out = String::new();
+        for x in (self.min.x..=self.max.x).rev() {
+            out.extend((self.min.y..=self.max.y).map(|y| cell(Pos { x, y, z: 0 })));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Draws the knots as in the puzzle's illustrations: `H` for the head, then
+/// `T` for the tail of a two-knot rope or the knot numbers otherwise, with
+/// `s` marking the start and earlier knots drawn over later ones.
+pub fn render_knots(knots: &[Pos], viewport: &Viewport) -> String {
+    let label = |i: usize| match i {
+        0 => 'H',
+        1 if knots.len() == 2 => 'T',
+        _ => char::from_digit(i as u32 % 36, 36).unwrap(),
+    };
+    viewport.render(|pos| {
+        knots
+            .iter()
+            .position(|&knot| knot.flat() == pos)
+            .map_or_else(|| if pos == START { 's' } else { '.' }, label)
+    })
+}
+
+/// Draws the positions the tail visited as `#`, with `s` marking the start.
+pub fn render_visited(visited: &HashSet<Pos>, viewport: &Viewport) -> String {
+    let visited: HashSet<Pos> = visited.iter().map(|pos| pos.flat()).collect();
+    viewport.render(|pos| match pos {
+        START => 's',
+        _ if visited.contains(&pos) => '#',
+        _ => '.',
+    })
+}
+
 #[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
-struct Pos {
+pub struct Pos {
     x: isize,
     y: isize,
+    z: isize,
+}
+
+impl Pos {
+    /// The position straight above or below this one on the `z = 0` plane,
+    /// for drawing 3D ropes.
+    fn flat(self) -> Self {
+        Pos { z: 0, ..self }
+    }
 }
 
 impl std::ops::Add for Pos {
@@ -50,113 +237,185 @@ impl std::ops::Add for Pos {
         Pos {
             x: self.x + rhs.x,
             y: self.y + rhs.y,
+            z: self.z + rhs.z,
         }
     }
 }
 
-impl From<&str> for Pos {
-    fn from(value: &str) -> Self {
-        match value {
-            "R" => RIGHT,
-            "U" => UP,
-            "D" => DOWN,
-            "L" => LEFT,
-            _ => panic!("invalid"),
+/// Whether moves may leave the plane with `F`orward and `B`ack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimensions {
+    Two,
+    Three,
+}
+
+/// Parses a direction of one letter per axis, so `U` and `R` can combine
+/// into the diagonal `UR`, and in 3D `URF` moves along all three at once.
+fn parse_direction(token: &str, dimensions: Dimensions) -> Result<Pos, ParseError<'_>> {
+    let mut direction = START;
+    let mut axes = [false; 3];
+    for (i, letter) in token.char_indices() {
+        let (axis, step) = match letter {
+            'U' => (0, UP),
+            'D' => (0, DOWN),
+            'R' => (1, RIGHT),
+            'L' => (1, LEFT),
+            'F' if dimensions == Dimensions::Three => (2, FORWARD),
+            'B' if dimensions == Dimensions::Three => (2, BACK),
+            _ => {
+                let reason = match dimensions {
+                    Dimensions::Two => "expected a direction of R, U, D or L",
+                    Dimensions::Three => "expected a direction of R, U, D, L, F or B",
+                };
+                return Err(ParseError::new(&token[i..i + letter.len_utf8()], reason));
+            }
+        };
+        if std::mem::replace(&mut axes[axis], true) {
+            return Err(ParseError::new(
+                &token[i..i + 1],
+                "expected at most one direction along each axis",
+            ));
         }
+        direction = direction + step;
+    }
+    if token.is_empty() {
+        return Err(ParseError::new(token, "expected a direction"));
     }
+    Ok(direction)
 }
 
+/// A rope whose knots each follow the one before, once it gets further away
+/// than their slack. Distance counts diagonals as one, so with a slack of 1 a
+/// knot only moves when it stops touching the one before.
 #[derive(Debug, Clone)]
-struct Rope {
-    knots: Vec<Cell<Pos>>,
+pub struct Rope {
+    knots: Vec<Pos>,
+    /// How far each knot after the head may lag behind the one before it.
+    slack: Vec<isize>,
 }
 
 impl Rope {
-    fn new(num_knots: usize) -> Self {
-        let knots = vec![Cell::new(Pos { x: 0, y: 0 }); num_knots];
-        Self { knots }
+    pub fn new(num_knots: usize) -> Self {
+        Self::with_slack(vec![1; num_knots.saturating_sub(1)])
+    }
+
+    /// A rope with one more knot than `slack` has entries, the first being
+    /// the slack between the head and the knot after it.
+    pub fn with_slack(slack: impl IntoIterator<Item = usize>) -> Self {
+        let slack: Vec<isize> = slack.into_iter().map(|s| s as isize).collect();
+        let knots = vec![START; slack.len() + 1];
+        Self { knots, slack }
+    }
+
+    /// The knots, head first.
+    pub fn knots(&self) -> &[Pos] {
+        &self.knots
     }
 
-    fn step(&self, direction: &Pos) {
-        self.knots[0].set(self.knots[0].get() + *direction);
+    pub fn step(&mut self, direction: &Pos) {
+        self.knots[0] = self.knots[0] + *direction;
         for i in 1..self.knots.len() {
-            let current_knot = self.knots[i].get();
-            let prior_knot = self.knots[i - 1].get();
-            let x_diff = prior_knot.x - current_knot.x;
-            let y_diff = prior_knot.y - current_knot.y;
-            if x_diff.abs() >= 2 || y_diff.abs() >= 2 {
-                self.knots[i].set(move_towards_prior(
-                    (prior_knot.x, prior_knot.y),
-                    (current_knot.x, current_knot.y),
-                ));
+            let prior = self.knots[i - 1];
+            let knot = &mut self.knots[i];
+            let diff = [prior.x - knot.x, prior.y - knot.y, prior.z - knot.z];
+            if diff.iter().any(|d| d.abs() > self.slack[i - 1]) {
+                knot.x += diff[0].signum();
+                knot.y += diff[1].signum();
+                knot.z += diff[2].signum();
             }
         }
     }
 }
 
-fn move_towards_prior(prior: (isize, isize), current: (isize, isize)) -> Pos {
-    let x_diff = prior.0 - current.0;
-    let y_diff = prior.1 - current.1;
-    match (x_diff, y_diff) {
-        (x, 0) if x > 0 => Pos {
-            x: current.0 + 1,
-            y: current.1,
-        },
-        (x, 0) if x < 0 => Pos {
-            x: current.0 - 1,
-            y: current.1,
-        },
-        (0, y) if y > 0 => Pos {
-            x: current.0,
-            y: current.1 + 1,
-        },
-        (0, y) if y < 0 => Pos {
-            x: current.0,
-            y: current.1 - 1,
-        },
-        (x, y) if x > 0 && y > 0 => Pos {
-            x: current.0 + 1,
-            y: current.1 + 1,
-        },
-        (x, y) if x < 0 && y < 0 => Pos {
-            x: current.0 - 1,
-            y: current.1 - 1,
-        },
-        (x, y) if x > 0 && y < 0 => Pos {
-            x: current.0 + 1,
-            y: current.1 - 1,
-        },
-        (x, y) if x < 0 && y > 0 => Pos {
-            x: current.0 - 1,
-            y: current.1 + 1,
-        },
-        _ => panic!("unreachable"),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-    use test::Bencher;
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) {
-        let input = util::read_input("inputs/day09.txt").unwrap();
-        b.iter(|| {
-            let moves = parse(input.clone());
-            let rope = Rope::new(2);
-            part_1(&moves, rope)
-        })
+    use crate::days::check_example;
+
+    #[test]
+    fn test_example() {
+        check_example(&Day09, include_str!("examples/day09.txt"), 13, 1);
+        check_example(&Day09, include_str!("examples/day09_larger.txt"), 88, 36);
     }
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) {
-        let input = util::read_input("inputs/day09.txt").unwrap();
-        b.iter(|| {
-            let moves = parse(input.clone());
-            let rope = Rope::new(10);
-            part_1(&moves, rope)
-        })
+    #[test]
+    fn test_trace() {
+        let moves = parse(include_str!("examples/day09.txt")).unwrap();
+        let trace = Trace::record(&moves, Rope::new(2));
+        assert_eq!(trace.frames().len(), 25);
+        assert_eq!(trace.visited().len(), 13);
+
+        let viewport = trace.bounds();
+        assert_eq!(
+            render_visited(&trace.visited(), &viewport),
+            "..##..\n...##.\n.####.\n....#.\ns###..\n"
+        );
+        assert_eq!(
+            render_knots(&trace.frames()[24], &viewport),
+            "......\n......\n.TH...\n......\ns.....\n"
+        );
+    }
+
+    #[test]
+    fn test_render_knots() {
+        let moves = parse("R 4\nU 4").unwrap();
+        let trace = Trace::record(&moves, Rope::new(10));
+        let viewport = Viewport {
+            min: START,
+            max: Pos { x: 4, y: 5, z: 0 },
+        };
+        assert_eq!(
+            render_knots(trace.frames().last().unwrap(), &viewport),
+            "....H.\n....1.\n..432.\n.5....\n6.....\n"
+        );
+        let centered = Viewport::centered(Pos { x: 4, y: 4, z: 0 }, 5, 6);
+        assert_eq!(
+            (centered.min, centered.max),
+            (Pos { x: 2, y: 2, z: 0 }, Pos { x: 6, y: 7, z: 0 })
+        );
+    }
+
+    #[test]
+    fn test_parse_moves() {
+        let moves = parse_moves("UR 2\nDLB 1", Dimensions::Three).unwrap();
+        assert_eq!(moves[0], (Pos { x: 1, y: 1, z: 0 }, 2));
+        assert_eq!(
+            moves[1],
+            (
+                Pos {
+                    x: -1,
+                    y: -1,
+                    z: -1
+                },
+                1
+            )
+        );
+
+        let reason = |input| {
+            parse_moves(input, Dimensions::Two)
+                .unwrap_err()
+                .reason()
+                .to_string()
+        };
+        assert_eq!(reason("UF 1"), "expected a direction of R, U, D or L");
+        assert_eq!(
+            reason("UD 1"),
+            "expected at most one direction along each axis"
+        );
+        assert_eq!(reason(" 1"), "expected a direction");
+    }
+
+    #[test]
+    fn test_slack() {
+        let mut rope = Rope::with_slack([2, 1]);
+        for _ in 0..3 {
+            rope.step(&RIGHT);
+        }
+        assert_eq!(rope.knots()[1], Pos { x: 0, y: 1, z: 0 });
+        assert_eq!(rope.knots()[2], START);
+
+        rope.step(&Pos { x: 1, y: 1, z: 1 });
+        assert_eq!(rope.knots()[1], Pos { x: 1, y: 2, z: 1 });
+        assert_eq!(rope.knots()[2], Pos { x: 1, y: 1, z: 1 });
     }
 }
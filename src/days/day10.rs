@@ -1,153 +1,1023 @@
-use std::collections::VecDeque;
+use super::Solution;
+use crate::answer::Answer;
+use crate::error::{parse_number, ParseError};
+use crate::ocr;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Write};
+use std::io;
+use std::str::FromStr;
 
-use crate::util;
+/// The cycles whose signal strengths part 1 adds up.
+pub const TARGET_CYCLES: [usize; 6] = [20, 60, 100, 140, 180, 220];
 
-const TARGET_CYCLES: [usize; 6] = [20, 60, 100, 140, 180, 220];
+/// How many cycles a program may run for by default before it is taken to
+/// be stuck in a loop.
+pub const DEFAULT_MAX_CYCLES: usize = 1_000_000;
 
-pub fn run() {
-    let raw_input = util::read_input("inputs/day10.txt").unwrap();
-    let mut input = parse(raw_input);
-    println!("part 1: {}", part_1(&mut input.clone()));
-    println!("part: 2");
-    for line in part_2(&mut input).iter() {
-        println!("{}", String::from_iter(line.iter()));
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<Instruction>;
+    type Part1 = i64;
+    type Part2 = Answer;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        parse(input)
+    }
+
+    fn part_1(&self, input: &Self::Input) -> i64 {
+        part_1(input)
+    }
+
+    /// The letters on the screen, or the screen itself for a human to read
+    /// if they are not all in the font.
+    fn part_2(&self, input: &Self::Input) -> Answer {
+        let screen = part_2(input);
+        match ocr::recognize(&screen) {
+            Ok(letters) => Answer::Text(letters),
+            Err(_) => screen.into(),
+        }
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-enum Instruction {
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Register {
+    X,
+    Y,
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Register::X => write!(f, "x"),
+            Register::Y => write!(f, "y"),
+        }
+    }
+}
+
+/// An instruction for the CPU. Beyond the puzzle's `noop` and `addx` there
+/// is a second register and control flow:
+///
+/// | instruction | cycles | effect                                      |
+/// |-------------|--------|---------------------------------------------|
+/// | `noop`      | 1      | nothing                                     |
+/// | `addx V`    | 2      | adds `V` to X                               |
+/// | `addy V`    | 2      | adds `V` to Y                               |
+/// | `jmp T`     | 2      | continues from instruction `T`              |
+/// | `jnz R T`   | 2      | continues from `T` if register `R` is not 0 |
+/// | `halt`      | 1      | stops the program                           |
+///
+/// Each takes effect at the end of its last cycle. Jump targets are
+/// instruction indices, and a target just past the last instruction ends the
+/// program.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Instruction {
     Noop,
     Addx(i64),
+    Addy(i64),
+    Jmp(usize),
+    Jnz(Register, usize),
+    Halt,
 }
 
-impl From<&str> for Instruction {
-    fn from(value: &str) -> Self {
-        let instruction_name = &value[0..4];
-        match instruction_name {
-            "noop" => Instruction::Noop,
-            "addx" => Instruction::Addx(value[5..].parse().unwrap()),
-            _ => panic!("invalid instruction"),
+impl Instruction {
+    /// How many cycles the instruction takes to complete.
+    pub fn cycles(&self) -> usize {
+        match self {
+            Instruction::Noop | Instruction::Halt => 1,
+            Instruction::Addx(_)
+            | Instruction::Addy(_)
+            | Instruction::Jmp(_)
+            | Instruction::Jnz(..) => 2,
+        }
+    }
+
+    /// Where the instruction may jump to, if anywhere.
+    pub fn target(&self) -> Option<usize> {
+        match self {
+            Instruction::Jmp(target) | Instruction::Jnz(_, target) => Some(*target),
+            _ => None,
+        }
+    }
+
+    /// Writes the instruction as assembly, with `target` naming the jump
+    /// target.
+    fn write_with_target(&self, f: &mut impl fmt::Write, target: impl fmt::Display) -> fmt::Result {
+        match self {
+            Instruction::Noop => write!(f, "noop"),
+            Instruction::Addx(amount) => write!(f, "addx {}", amount),
+            Instruction::Addy(amount) => write!(f, "addy {}", amount),
+            Instruction::Jmp(_) => write!(f, "jmp {}", target),
+            Instruction::Jnz(register, _) => write!(f, "jnz {} {}", register, target),
+            Instruction::Halt => write!(f, "halt"),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum CPUState {
-    Idle,
-    Busy,
+/// Shows jump targets as instruction indices, which [`assemble`] accepts
+/// as well as labels.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with_target(f, self.target().unwrap_or_default())
+    }
 }
 
-#[derive(Debug)]
-struct Cpu {
-    x_register: i64,
-    cycle_count: usize,
-    curr_state: CPUState,
-    curr_instruction: Option<Instruction>,
+/// Assembles a program of one instruction per line. A `;` starts a comment,
+/// and a line can begin with a `label:` for jumps to use in place of an
+/// instruction index.
+pub fn assemble(source: &str) -> Result<Vec<Instruction>, ParseError<'_>> {
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    let mut code = Vec::new();
+    for line in source.lines() {
+        let line = line.split(';').next().unwrap_or_default().trim();
+        let line = match line.split_once(':') {
+            Some((label, rest)) => {
+                let label = label.trim_end();
+                if !is_label(label) {
+                    return Err(ParseError::new(
+                        label,
+                        "expected a label of letters, digits and `_`, not starting with a digit",
+                    ));
+                }
+                if labels.insert(label, code.len()).is_some() {
+                    return Err(ParseError::new(
+                        label,
+                        "expected each label to be defined once",
+                    ));
+                }
+                rest.trim_start()
+            }
+            None => line,
+        };
+        if !line.is_empty() {
+            code.push(line);
+        }
+    }
+    code.iter()
+        .map(|line| parse_instruction(line, &labels, code.len()))
+        .collect()
+}
+
+fn is_label(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_instruction<'a>(
+    line: &'a str,
+    labels: &HashMap<&str, usize>,
+    len: usize,
+) -> Result<Instruction, ParseError<'a>> {
+    let mut words = line.split_whitespace();
+    let mnemonic = words.next().expect("blank lines are skipped");
+    let operands: Vec<&str> = words.collect();
+    let usage = match mnemonic {
+        "noop" | "halt" => mnemonic,
+        "addx" => "addx <value>",
+        "addy" => "addy <value>",
+        "jmp" => "jmp <target>",
+        "jnz" => "jnz <register> <target>",
+        _ => {
+            return Err(ParseError::new(
+                mnemonic,
+                "expected an instruction: noop, addx, addy, jmp, jnz or halt",
+            ))
+        }
+    };
+    if operands.len() != usage.split(' ').count() - 1 {
+        return Err(ParseError::new(line, format!("expected `{}`", usage)));
+    }
+    let target = |operand: &'a str| {
+        let index = if operand.starts_with(|c: char| c.is_ascii_digit()) {
+            parse_number(operand, "a jump target")?
+        } else {
+            *labels
+                .get(operand)
+                .ok_or_else(|| ParseError::new(operand, "expected a defined label"))?
+        };
+        if index > len {
+            return Err(ParseError::new(
+                operand,
+                format!("expected a jump target of at most {}", len),
+            ));
+        }
+        Ok(index)
+    };
+    Ok(match mnemonic {
+        "noop" => Instruction::Noop,
+        "addx" => Instruction::Addx(parse_number(operands[0], "an integer")?),
+        "addy" => Instruction::Addy(parse_number(operands[0], "an integer")?),
+        "jmp" => Instruction::Jmp(target(operands[0])?),
+        "jnz" => {
+            let register = match operands[0] {
+                "x" => Register::X,
+                "y" => Register::Y,
+                other => return Err(ParseError::new(other, "expected a register, x or y")),
+            };
+            Instruction::Jnz(register, target(operands[1])?)
+        }
+        _ => Instruction::Halt,
+    })
+}
+
+/// Writes a program back out as assembly that [`assemble`]s to the same
+/// instructions, with a label `l<index>` at each jump target.
+pub fn disassemble(program: &[Instruction]) -> String {
+    let targets: BTreeSet<usize> = program.iter().filter_map(Instruction::target).collect();
+    let mut out = String::new();
+    for (index, instruction) in program.iter().enumerate() {
+        if targets.contains(&index) {
+            writeln!(out, "l{}:", index).unwrap();
+        }
+        out.push_str("    ");
+        let label = format!("l{}", instruction.target().unwrap_or_default());
+        instruction.write_with_target(&mut out, label).unwrap();
+        out.push('\n');
+    }
+    if targets.contains(&program.len()) {
+        writeln!(out, "l{}:", program.len()).unwrap();
+    }
+    out
+}
+
+/// The state of the CPU during a cycle, before the instruction in progress
+/// has taken effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// The cycle number, counting from 1.
+    pub number: usize,
+    pub x: i64,
+    pub y: i64,
+    /// Index of the instruction in progress.
+    pub pc: usize,
+    pub instruction: Instruction,
+    /// How many cycles of the instruction have already passed.
+    pub stage: usize,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cycle {:>4}  x {:>4}  y {:>4}  pc {:>4}  {}",
+            self.number, self.x, self.y, self.pc, self.instruction
+        )?;
+        if self.stage > 0 {
+            write!(f, " ({}/{})", self.stage + 1, self.instruction.cycles())?;
+        }
+        Ok(())
+    }
 }
 
-impl Cpu {
-    fn new() -> Self {
+/// Something that watches the CPU run, told about each cycle in turn.
+pub trait Observer {
+    fn on_cycle(&mut self, cycle: &Cycle);
+}
+
+impl<F: FnMut(&Cycle)> Observer for F {
+    fn on_cycle(&mut self, cycle: &Cycle) {
+        self(cycle)
+    }
+}
+
+/// What a program did by the time it halted, ran out of instructions, or
+/// used up its cycle budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    pub cycles: usize,
+    /// How many instructions were carried out, counting each time round a
+    /// loop.
+    pub instructions: usize,
+    /// The registers once the last instruction has finished.
+    pub x: i64,
+    pub y: i64,
+    /// Whether the program was stopped at the cycle limit rather than
+    /// finishing.
+    pub limit_reached: bool,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} instructions in {} cycles, x = {}, y = {}",
+            self.instructions, self.cycles, self.x, self.y
+        )?;
+        if self.limit_reached {
+            write!(f, ", stopped at the cycle limit")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cpu<'a> {
+    program: &'a [Instruction],
+    x: i64,
+    y: i64,
+    pc: usize,
+    cycle: usize,
+    stage: usize,
+    executed: usize,
+    halted: bool,
+}
+
+impl<'a> Cpu<'a> {
+    pub fn new(program: &'a [Instruction]) -> Self {
         Self {
-            x_register: 1,
-            cycle_count: 0,
-            curr_state: CPUState::Idle,
-            curr_instruction: None,
+            program,
+            x: 1,
+            y: 0,
+            pc: 0,
+            cycle: 0,
+            stage: 0,
+            executed: 0,
+            halted: false,
         }
     }
 
-    fn is_busy(&self) -> bool {
-        if self.curr_state == CPUState::Busy {
-            return true;
+    pub fn x(&self) -> i64 {
+        self.x
+    }
+
+    pub fn y(&self) -> i64 {
+        self.y
+    }
+
+    pub fn register(&self, register: Register) -> i64 {
+        match register {
+            Register::X => self.x,
+            Register::Y => self.y,
         }
-        false
     }
 
-    fn load_instruction(&mut self, instruction: &Instruction) -> Option<()> {
-        // load instruction begins a cycle
-        self.cycle_count += 1;
-        if self.curr_instruction.is_some() {
+    /// The number of cycles completed so far.
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// The instruction in progress, or about to start.
+    pub fn instruction(&self) -> Option<Instruction> {
+        if self.halted {
             return None;
         }
-        self.curr_instruction = Some(instruction.to_owned());
-        Some(())
+        self.program.get(self.pc).copied()
+    }
+
+    /// How many cycles of the current instruction have already passed.
+    pub fn stage(&self) -> usize {
+        self.stage
     }
 
-    fn tick(&mut self) {
-        if self.is_busy() {
-            if let Some(instruction) = self.curr_instruction {
-                match instruction {
-                    Instruction::Addx(val) => self.x_register += val,
-                    Instruction::Noop => (), // this should never happen
+    /// Whether the program has run `halt` or off its end.
+    pub fn is_halted(&self) -> bool {
+        self.halted || self.pc >= self.program.len()
+    }
+
+    /// Runs a single cycle, returning the state during it, or `None` once
+    /// the program has finished.
+    pub fn step(&mut self) -> Option<Cycle> {
+        if self.halted {
+            return None;
+        }
+        let instruction = *self.program.get(self.pc)?;
+        self.cycle += 1;
+        let cycle = Cycle {
+            number: self.cycle,
+            x: self.x,
+            y: self.y,
+            pc: self.pc,
+            instruction,
+            stage: self.stage,
+        };
+        self.stage += 1;
+        if self.stage == instruction.cycles() {
+            self.stage = 0;
+            self.executed += 1;
+            self.pc += 1;
+            match instruction {
+                Instruction::Noop => {}
+                Instruction::Addx(amount) => self.x += amount,
+                Instruction::Addy(amount) => self.y += amount,
+                Instruction::Jmp(target) => self.pc = target,
+                Instruction::Jnz(register, target) => {
+                    if self.register(register) != 0 {
+                        self.pc = target;
+                    }
                 }
-                self.curr_state = CPUState::Idle;
-                self.curr_instruction = None;
+                Instruction::Halt => self.halted = true,
             }
-        } else {
-            match self.curr_instruction.unwrap() {
-                Instruction::Addx(_) => self.curr_state = CPUState::Busy,
-                _ => self.curr_instruction = None,
+        }
+        Some(cycle)
+    }
+
+    /// Runs the rest of the program, showing every cycle to each observer.
+    /// Since jumps allow programs that never end, it stops once
+    /// `max_cycles` cycles have run in total.
+    pub fn run(&mut self, max_cycles: usize, observers: &mut [&mut dyn Observer]) -> Summary {
+        while self.cycle < max_cycles {
+            let Some(cycle) = self.step() else {
+                break;
+            };
+            for observer in observers.iter_mut() {
+                observer.on_cycle(&cycle);
             }
         }
+        Summary {
+            cycles: self.cycle,
+            instructions: self.executed,
+            x: self.x,
+            y: self.y,
+            limit_reached: !self.is_halted(),
+        }
     }
 }
 
-fn parse(input: String) -> VecDeque<Instruction> {
-    input.lines().map(Instruction::from).collect()
+/// Adds up the signal strength, the cycle number times X, during each of a
+/// set of cycles.
+#[derive(Debug, Clone, Default)]
+pub struct SignalSampler {
+    cycles: Vec<usize>,
+    /// The signal strength during each sampled cycle the program reached.
+    pub samples: Vec<(usize, i64)>,
+}
+
+impl SignalSampler {
+    pub fn new(cycles: &[usize]) -> Self {
+        Self {
+            cycles: cycles.to_vec(),
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn total(&self) -> i64 {
+        self.samples.iter().map(|(_, strength)| strength).sum()
+    }
 }
 
-fn part_1(instructions: &mut VecDeque<Instruction>) -> i64 {
-    let mut signal_strength = 0;
-    let mut cpu = Cpu::new();
-    for _ in 0..220 {
-        // try to load next instruction
-        let next_instruction = instructions.pop_front().unwrap();
-        match cpu.load_instruction(&next_instruction) {
-            Some(_) => (),
-            // instruction already loaded, put it back on the queue
-            None => instructions.push_front(next_instruction),
+impl Observer for SignalSampler {
+    fn on_cycle(&mut self, cycle: &Cycle) {
+        if self.cycles.contains(&cycle.number) {
+            self.samples
+                .push((cycle.number, cycle.number as i64 * cycle.x));
         }
+    }
+}
+
+/// A screen drawn one pixel per cycle, left to right and top to bottom. A
+/// pixel is lit when the three pixel wide sprite centred on X covers it.
+#[derive(Debug, Clone)]
+pub struct Crt {
+    width: usize,
+    pixels: Vec<Vec<char>>,
+}
 
-        if TARGET_CYCLES.contains(&cpu.cycle_count) {
-            signal_strength += cpu.cycle_count as i64 * cpu.x_register;
+impl Crt {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            pixels: vec![vec![' '; width]; height],
         }
+    }
 
-        cpu.tick();
+    pub fn screen(&self) -> &[Vec<char>] {
+        &self.pixels
     }
-    signal_strength
+
+    pub fn into_screen(self) -> Vec<Vec<char>> {
+        self.pixels
+    }
+}
+
+impl Observer for Crt {
+    fn on_cycle(&mut self, cycle: &Cycle) {
+        let row = (cycle.number - 1) / self.width;
+        let col = (cycle.number - 1) % self.width;
+        if let Some(pixels) = self.pixels.get_mut(row) {
+            if (cycle.x - 1..=cycle.x + 1).contains(&(col as i64)) {
+                pixels[col] = '#';
+            }
+        }
+    }
+}
+
+/// Writes each cycle as a line of trace as soon as it runs.
+#[derive(Debug)]
+pub struct Tracer<W> {
+    out: W,
+    /// The first write that failed. Nothing more is written after it.
+    result: io::Result<()>,
+}
+
+impl<W: io::Write> Tracer<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            result: Ok(()),
+        }
+    }
+
+    /// Flushes the trace, returning the writer or the first write error.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.result?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+impl<W: io::Write> Observer for Tracer<W> {
+    fn on_cycle(&mut self, cycle: &Cycle) {
+        if self.result.is_ok() {
+            self.result = writeln!(self.out, "{}", cycle);
+        }
+    }
+}
+
+/// A command for the [`Debugger`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Run this many cycles.
+    Step(usize),
+    /// Run until this many instructions have finished.
+    Next(usize),
+    /// Run until a breakpoint or the end of the program.
+    Continue,
+    /// Stop before the given cycle starts.
+    BreakAt(usize),
+    /// Stop whenever X changes.
+    WatchX,
+    /// Remove all breakpoints and the watch on X.
+    Delete,
+    Registers,
+    Crt,
+    List,
+    Help,
+    Quit,
 }
 
-fn part_2(instructions: &mut VecDeque<Instruction>) -> Vec<Vec<char>> {
-    let mut crt = vec![vec![' '; 40]; 6];
-    let mut cpu = Cpu::new();
-    for _ in 0..240 {
-        let next_instruction = instructions.pop_front().unwrap();
-        match cpu.load_instruction(&next_instruction) {
-            Some(_) => (),
-            None => instructions.push_front(next_instruction),
+pub const DEBUGGER_HELP: &str = "\
+s, step [N]       run N cycles (default 1)
+n, next [N]       run until N instructions have finished (default 1)
+c, continue       run until a breakpoint, the end of the program or the cycle limit
+b, break <CYCLE>  stop before cycle CYCLE starts
+b, break x        stop whenever X changes
+d, delete         remove all breakpoints
+r, regs           show the registers and the next instruction
+crt               show the CRT screen drawn so far
+l, list           show the program around the next instruction
+h, help           show this help
+q, quit           leave the debugger
+An empty line repeats the last command.
+";
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let command = words.next().ok_or("expected a command, try `help`")?;
+        let argument = words.next();
+        if let Some(extra) = words.next() {
+            return Err(format!("unexpected `{}`", extra));
+        }
+        let count = || match argument {
+            None => Ok(1),
+            Some(count) => count
+                .parse()
+                .map_err(|_| format!("expected a count, found `{}`", count)),
+        };
+        let command =
+            match command {
+                "s" | "step" => Command::Step(count()?),
+                "n" | "next" => Command::Next(count()?),
+                "b" | "break" => match argument {
+                    Some("x") => Command::WatchX,
+                    Some(cycle) => Command::BreakAt(cycle.parse().map_err(|_| {
+                        format!("expected a cycle number or `x`, found `{}`", cycle)
+                    })?),
+                    None => return Err("expected `break <cycle>` or `break x`".to_string()),
+                },
+                "c" | "continue" => Command::Continue,
+                "d" | "delete" => Command::Delete,
+                "r" | "regs" => Command::Registers,
+                "crt" => Command::Crt,
+                "l" | "list" => Command::List,
+                "h" | "help" => Command::Help,
+                "q" | "quit" => Command::Quit,
+                _ => return Err(format!("unknown command `{}`, try `help`", command)),
+            };
+        match (command, argument) {
+            (Command::Step(_) | Command::Next(_) | Command::BreakAt(_) | Command::WatchX, _)
+            | (_, None) => Ok(command),
+            (_, Some(argument)) => Err(format!("unexpected `{}`", argument)),
+        }
+    }
+}
+
+/// Runs a program a little at a time, drawing the CRT as it goes.
+#[derive(Debug, Clone)]
+pub struct Debugger<'a> {
+    cpu: Cpu<'a>,
+    program: &'a [Instruction],
+    crt: Crt,
+    breakpoints: BTreeSet<usize>,
+    /// The breakpoint the last `continue` stopped at, so that the next one
+    /// runs past it.
+    stopped_at: Option<usize>,
+    watch_x: bool,
+    /// How many cycles a single `continue` may run, so that a program stuck
+    /// in a loop hands control back.
+    max_cycles: usize,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(program: &'a [Instruction], max_cycles: usize) -> Self {
+        Self {
+            cpu: Cpu::new(program),
+            program,
+            crt: Crt::new(40, 6),
+            breakpoints: BTreeSet::new(),
+            stopped_at: None,
+            watch_x: false,
+            max_cycles,
+        }
+    }
+
+    pub fn cpu(&self) -> &Cpu<'a> {
+        &self.cpu
+    }
+
+    /// Carries out a command, returning what to show for it, or why it
+    /// cannot be carried out. [`Command::Quit`] is left to the caller.
+    pub fn execute(&mut self, command: Command) -> Result<String, String> {
+        let mut out = String::new();
+        match command {
+            Command::Step(count) => {
+                for _ in 0..count {
+                    match self.advance() {
+                        Some(cycle) => writeln!(out, "{}", cycle).unwrap(),
+                        None => break,
+                    }
+                }
+                out.push_str(&self.status());
+            }
+            Command::Next(count) => {
+                for _ in 0..count {
+                    while let Some(cycle) = self.advance() {
+                        if cycle.stage + 1 == cycle.instruction.cycles() {
+                            break;
+                        }
+                    }
+                }
+                out.push_str(&self.status());
+            }
+            Command::Continue => {
+                for ran in 0.. {
+                    let next = self.cpu.cycle() + 1;
+                    if self.breakpoints.contains(&next)
+                        && (ran > 0 || self.stopped_at != Some(next))
+                    {
+                        writeln!(out, "breakpoint before cycle {}", next).unwrap();
+                        self.stopped_at = Some(next);
+                        break;
+                    }
+                    if ran == self.max_cycles {
+                        writeln!(out, "stopped after {} cycles", ran).unwrap();
+                        break;
+                    }
+                    let x = self.cpu.x();
+                    if self.advance().is_none() {
+                        break;
+                    }
+                    if self.watch_x && self.cpu.x() != x {
+                        writeln!(out, "x changed from {} to {}", x, self.cpu.x()).unwrap();
+                        break;
+                    }
+                }
+                out.push_str(&self.status());
+            }
+            Command::BreakAt(cycle) => {
+                if cycle <= self.cpu.cycle() {
+                    return Err(format!("expected a cycle after {}", self.cpu.cycle()));
+                }
+                self.breakpoints.insert(cycle);
+                writeln!(out, "breakpoint before cycle {}", cycle).unwrap();
+            }
+            Command::WatchX => {
+                self.watch_x = true;
+                writeln!(out, "watching x").unwrap();
+            }
+            Command::Delete => {
+                self.breakpoints.clear();
+                self.watch_x = false;
+                writeln!(out, "breakpoints removed").unwrap();
+            }
+            Command::Registers => out.push_str(&self.status()),
+            Command::Crt => out.push_str(&self.render_crt()),
+            Command::List => out.push_str(&self.list()),
+            Command::Help => out.push_str(DEBUGGER_HELP),
+            Command::Quit => {}
+        }
+        Ok(out)
+    }
+
+    fn advance(&mut self) -> Option<Cycle> {
+        let cycle = self.cpu.step()?;
+        self.crt.on_cycle(&cycle);
+        Some(cycle)
+    }
+
+    /// The cycles run, the registers, and what comes next.
+    pub fn status(&self) -> String {
+        let cpu = &self.cpu;
+        let mut out = format!(
+            "after cycle {}: x {}  y {}  ",
+            cpu.cycle(),
+            cpu.x(),
+            cpu.y()
+        );
+        match cpu.instruction() {
+            Some(instruction) => {
+                write!(out, "next {}: {}", cpu.pc(), instruction).unwrap();
+                if cpu.stage() > 0 {
+                    write!(out, " ({}/{})", cpu.stage() + 1, instruction.cycles()).unwrap();
+                }
+            }
+            None => out.push_str("halted"),
         }
-        let curr_row = cpu.cycle_count / 40;
-        let curr_col = (cpu.cycle_count - 1) % 40;
-        let curr_col_i64 = curr_col as i64;
-        if (cpu.x_register - 1..=cpu.x_register + 1).contains(&curr_col_i64) {
-            crt[curr_row][curr_col] = '#';
+        out.push('\n');
+        out
+    }
+
+    /// The screen so far, with `#` for lit pixels and `.` for dark ones,
+    /// and pixels still to be drawn left blank.
+    pub fn render_crt(&self) -> String {
+        let mut out = String::new();
+        let mut index = 0;
+        for row in self.crt.screen() {
+            for &pixel in row {
+                out.push(match pixel {
+                    _ if index >= self.cpu.cycle() => ' ',
+                    '#' => '#',
+                    _ => '.',
+                });
+                index += 1;
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// A few instructions either side of the next one, which is marked.
+    fn list(&self) -> String {
+        let pc = self.cpu.pc();
+        let start = pc.saturating_sub(5);
+        let end = (pc + 6).min(self.program.len());
+        let mut out = String::new();
+        for (index, instruction) in self.program.iter().enumerate().take(end).skip(start) {
+            let marker = if index == pc { "=>" } else { "  " };
+            writeln!(out, "{} {:>4}  {}", marker, index, instruction).unwrap();
         }
-        cpu.tick();
+        out
     }
-    crt
+}
+
+pub fn parse(input: &str) -> Result<Vec<Instruction>, ParseError<'_>> {
+    assemble(input)
+}
+
+fn part_1(program: &[Instruction]) -> i64 {
+    let mut sampler = SignalSampler::new(&TARGET_CYCLES);
+    Cpu::new(program).run(TARGET_CYCLES[TARGET_CYCLES.len() - 1], &mut [&mut sampler]);
+    sampler.total()
+}
+
+fn part_2(program: &[Instruction]) -> Vec<Vec<char>> {
+    let mut crt = Crt::new(40, 6);
+    Cpu::new(program).run(40 * 6, &mut [&mut crt]);
+    crt.into_screen()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-    use test::Bencher;
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) {
-        let raw_input = util::read_input("inputs/day10.txt").unwrap();
-        b.iter(|| {
-            let mut input = parse(raw_input.clone());
-            part_1(&mut input);
-        })
+    use crate::days::check_example;
+
+    #[test]
+    fn test_example() {
+        let crt = Answer::Grid(
+            [
+                "##..##..##..##..##..##..##..##..##..##..",
+                "###...###...###...###...###...###...###.",
+                "####....####....####....####....####....",
+                "#####.....#####.....#####.....#####.....",
+                "######......######......######......####",
+                "#######.......#######.......#######.....",
+            ]
+            .map(|row| row.replace('.', " "))
+            .to_vec(),
+        );
+        check_example(&Day10, include_str!("examples/day10.txt"), 13140, crt);
+    }
+
+    #[test]
+    fn test_run_to_completion() {
+        let program = parse("noop\naddx 3\naddx -5").unwrap();
+        let mut sampler = SignalSampler::new(&TARGET_CYCLES);
+        let mut tracer = Tracer::new(Vec::new());
+        let mut xs = Vec::new();
+        let mut record_x = |cycle: &Cycle| xs.push(cycle.x);
+        let summary = Cpu::new(&program).run(
+            DEFAULT_MAX_CYCLES,
+            &mut [&mut sampler, &mut tracer, &mut record_x],
+        );
+
+        assert_eq!(
+            summary,
+            Summary {
+                cycles: 5,
+                instructions: 3,
+                x: -1,
+                y: 0,
+                limit_reached: false
+            }
+        );
+        assert_eq!(xs, [1, 1, 1, 4, 4]);
+        assert!(sampler.samples.is_empty());
+        let trace = String::from_utf8(tracer.finish().unwrap()).unwrap();
+        assert_eq!(
+            trace.lines().nth(4),
+            Some("cycle    5  x    4  y    0  pc    2  addx -5 (2/2)")
+        );
+    }
+
+    #[test]
+    fn test_assemble() {
+        let source = "\
+; count y down from 3, adding 2 to x each time
+        addy 3
+loop:   addx 2      ; two cycles
+        addy -1
+        jnz y loop
+        halt
+        addx 100    ; never reached
+";
+        let program = assemble(source).unwrap();
+        assert_eq!(program[3], Instruction::Jnz(Register::Y, 1));
+        let summary = Cpu::new(&program).run(DEFAULT_MAX_CYCLES, &mut []);
+        assert_eq!(
+            summary,
+            Summary {
+                cycles: 2 + 3 * 6 + 1,
+                instructions: 1 + 3 * 3 + 1,
+                x: 7,
+                y: 0,
+                limit_reached: false
+            }
+        );
+
+        let disassembled = disassemble(&program);
+        assert_eq!(
+            disassembled,
+            "    addy 3\nl1:\n    addx 2\n    addy -1\n    jnz y l1\n    halt\n    addx 100\n"
+        );
+        assert_eq!(assemble(&disassembled), Ok(program));
+        assert_eq!(
+            assemble("jmp 0\njmp 2"),
+            Ok(vec![Instruction::Jmp(0), Instruction::Jmp(2)])
+        );
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        let reason = |source| assemble(source).unwrap_err().reason().to_string();
+        assert_eq!(
+            reason("mul 3"),
+            "expected an instruction: noop, addx, addy, jmp, jnz or halt"
+        );
+        assert_eq!(reason("addx"), "expected `addx <value>`");
+        assert_eq!(reason("noop 1"), "expected `noop`");
+        assert_eq!(reason("jmp nowhere"), "expected a defined label");
+        assert_eq!(reason("jnz z 0"), "expected a register, x or y");
+        assert_eq!(reason("jmp 2"), "expected a jump target of at most 1");
+        assert_eq!(
+            reason("a:\na: noop"),
+            "expected each label to be defined once"
+        );
+        assert_eq!(
+            reason("1a: noop"),
+            "expected a label of letters, digits and `_`, not starting with a digit"
+        );
+    }
+
+    #[test]
+    fn test_debugger() {
+        let program = parse(include_str!("examples/day10.txt")).unwrap();
+        let mut debugger = Debugger::new(&program, DEFAULT_MAX_CYCLES);
+        let mut run = |command: &str| debugger.execute(command.parse().unwrap()).unwrap();
+
+        assert_eq!(
+            run("step 2"),
+            "cycle    1  x    1  y    0  pc    0  addx 15\n\
+             cycle    2  x    1  y    0  pc    0  addx 15 (2/2)\n\
+             after cycle 2: x 16  y 0  next 1: addx -11\n"
+        );
+        assert_eq!(
+            run("s"),
+            "cycle    3  x   16  y    0  pc    1  addx -11\n\
+             after cycle 3: x 16  y 0  next 1: addx -11 (2/2)\n"
+        );
+        run("break 20");
+        assert_eq!(
+            run("continue"),
+            "breakpoint before cycle 20\nafter cycle 19: x 21  y 0  next 10: addx -1\n"
+        );
+        run("delete");
+        run("break x");
+        assert_eq!(
+            run("c"),
+            "x changed from 21 to 20\nafter cycle 21: x 20  y 0  next 11: addx 5\n"
+        );
+        assert_eq!(
+            run("next 2"),
+            "after cycle 25: x 24  y 0  next 13: addx 5\n"
+        );
+        assert!(run("crt").starts_with("##..##..##..##..##..##..#    "));
+        assert!(run("list").contains("=>   13  addx 5\n"));
+
+        run("delete");
+        assert!(run("c").ends_with("after cycle 240: x 17  y 0  halted\n"));
+        assert_eq!(run("s").lines().count(), 1);
+    }
+
+    #[test]
+    fn test_debugger_breakpoints() {
+        let program = parse(include_str!("examples/day10.txt")).unwrap();
+        let mut debugger = Debugger::new(&program, DEFAULT_MAX_CYCLES);
+        assert_eq!(
+            debugger.execute(Command::BreakAt(0)),
+            Err("expected a cycle after 0".to_string())
+        );
+        debugger.execute(Command::Step(5)).unwrap();
+        assert!(debugger.execute(Command::BreakAt(5)).is_err());
+        debugger.execute(Command::BreakAt(6)).unwrap();
+        assert_eq!(
+            debugger.execute(Command::Continue),
+            Ok(
+                "breakpoint before cycle 6\nafter cycle 5: x 5  y 0  next 2: addx 6 (2/2)\n"
+                    .to_string()
+            )
+        );
+        assert!(debugger
+            .execute(Command::Continue)
+            .unwrap()
+            .ends_with("halted\n"));
+    }
+
+    #[test]
+    fn test_debugger_commands() {
+        assert_eq!("n 3".parse(), Ok(Command::Next(3)));
+        assert_eq!("b x".parse(), Ok(Command::WatchX));
+        assert_eq!("break 20".parse(), Ok(Command::BreakAt(20)));
+        assert_eq!(
+            "step x".parse::<Command>(),
+            Err("expected a count, found `x`".to_string())
+        );
+        assert_eq!(
+            "regs 2".parse::<Command>(),
+            Err("unexpected `2`".to_string())
+        );
+        assert_eq!(
+            "jump".parse::<Command>(),
+            Err("unknown command `jump`, try `help`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cycle_limit() {
+        let program = parse("noop\njmp 0").unwrap();
+        let summary = Cpu::new(&program).run(1000, &mut []);
+        assert_eq!((summary.cycles, summary.limit_reached), (1000, true));
+        assert!(summary.to_string().ends_with("stopped at the cycle limit"));
+        assert_eq!(
+            Day10.part_1(&program),
+            TARGET_CYCLES.iter().sum::<usize>() as i64
+        );
+        assert_eq!(part_2(&program)[5].len(), 40);
+    }
+
+    #[test]
+    fn test_debugger_cycle_limit() {
+        let program = parse("jmp 0").unwrap();
+        let mut debugger = Debugger::new(&program, 100);
+        assert_eq!(
+            debugger.execute(Command::Continue).unwrap(),
+            "stopped after 100 cycles\nafter cycle 100: x 1  y 0  next 0: jmp 0\n"
+        );
+        assert_eq!(debugger.cpu().cycle(), 100);
     }
 }
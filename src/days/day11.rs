@@ -1,11 +1,25 @@
-use crate::util;
+use super::Solution;
+use crate::error::{parse_number, ParseError};
 use std::collections::VecDeque;
 
-pub fn run() {
-    let raw_input = util::read_input("inputs/day11.txt").unwrap();
-    let mut monkeys = parse(raw_input);
-    println!("part 1: {}", part_1(&mut monkeys.clone()));
-    println!("part 2: {}", part_2(&mut monkeys));
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Monkeys;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        parse(input)
+    }
+
+    fn part_1(&self, input: &Self::Input) -> usize {
+        part_1(&mut input.clone())
+    }
+
+    fn part_2(&self, input: &Self::Input) -> usize {
+        part_2(&mut input.clone())
+    }
 }
 
 fn part_2(monkeys: &mut Monkeys) -> usize {
@@ -18,14 +32,27 @@ fn part_1(monkeys: &mut Monkeys) -> usize {
     monkeys.monkey_business()
 }
 
-fn parse(input: String) -> Monkeys {
-    let monkeys = input.split("\n\n").map(Monkey::from).collect();
+fn parse(input: &str) -> Result<Monkeys, ParseError<'_>> {
+    let blocks: Vec<&str> = input.split("\n\n").collect();
+    let monkeys = blocks
+        .iter()
+        .map(|&block| {
+            let monkey = Monkey::try_from(block)?;
+            if monkey.op_true >= blocks.len() || monkey.op_false >= blocks.len() {
+                return Err(ParseError::new(
+                    block.lines().next().unwrap_or(block),
+                    "monkey throws to a monkey that does not exist",
+                ));
+            }
+            Ok(monkey)
+        })
+        .collect::<Result<_, _>>()?;
 
-    Monkeys { monkeys }
+    Ok(Monkeys { monkeys })
 }
 
 #[derive(Clone)]
-struct Monkeys {
+pub struct Monkeys {
     monkeys: Vec<Monkey>,
 }
 
@@ -38,7 +65,7 @@ impl Monkeys {
                 for _ in 0..monkey.items.len() {
                     monkey.items_inspected += 1;
                     let item = monkey.op.apply(monkey.items.pop_front().unwrap()) % worry_reducer;
-                    if item % monkey.test == 0 {
+                    if item.is_multiple_of(monkey.test) {
                         self.monkeys[monkey.op_true].items.push_back(item);
                     } else {
                         self.monkeys[monkey.op_false].items.push_back(item);
@@ -51,7 +78,7 @@ impl Monkeys {
 
     fn monkey_business(&self) -> usize {
         let mut monkeys = self.monkeys.clone();
-        monkeys.sort_by(|x, y| y.items_inspected.cmp(&x.items_inspected));
+        monkeys.sort_by_key(|monkey| std::cmp::Reverse(monkey.items_inspected));
 
         monkeys
             .iter()
@@ -71,50 +98,44 @@ struct Monkey {
     items: VecDeque<usize>,
 }
 
-impl From<&str> for Monkey {
-    fn from(value: &str) -> Self {
+impl<'a> TryFrom<&'a str> for Monkey {
+    type Error = ParseError<'a>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
         let mut monkey_lines = value.lines().skip(1);
-        let items_str = monkey_lines.next().unwrap();
-        let (_, items) = items_str.split_once(':').unwrap();
-        let items: VecDeque<usize> = items
-            .trim()
-            .split(", ")
-            .map(|item| item.parse().unwrap())
-            .collect();
-        let op: Op = monkey_lines.next().unwrap().into();
-        let test = monkey_lines
-            .next()
-            .unwrap()
-            .split_ascii_whitespace()
-            .last()
-            .unwrap()
-            .parse()
-            .unwrap();
-        let op_true = monkey_lines
-            .next()
-            .unwrap()
-            .split_ascii_whitespace()
-            .last()
-            .unwrap()
-            .parse()
-            .unwrap();
-        let op_false = monkey_lines
-            .next()
-            .unwrap()
-            .split_ascii_whitespace()
-            .last()
-            .unwrap()
-            .parse()
-            .unwrap();
-
-        Self {
+        let mut field = |prefix: &str| -> Result<&'a str, ParseError<'a>> {
+            let line = monkey_lines
+                .next()
+                .ok_or_else(|| ParseError::after(value, format!("expected `{}`", prefix)))?;
+            line.trim()
+                .strip_prefix(prefix)
+                .map(str::trim)
+                .ok_or_else(|| ParseError::new(line, format!("expected `{}`", prefix)))
+        };
+
+        let items: VecDeque<usize> = field("Starting items:")?
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| parse_number(item, "a worry level"))
+            .collect::<Result<_, _>>()?;
+        let op = Op::try_from(field("Operation: new = old")?)?;
+        let test_str = field("Test: divisible by")?;
+        let test = parse_number(test_str, "a divisor")?;
+        if test == 0 {
+            return Err(ParseError::new(test_str, "expected a non-zero divisor"));
+        }
+        let op_true = parse_number(field("If true: throw to monkey")?, "a monkey number")?;
+        let op_false = parse_number(field("If false: throw to monkey")?, "a monkey number")?;
+
+        Ok(Self {
             items_inspected: 0,
             test,
             op,
             op_true,
             op_false,
             items,
-        }
+        })
     }
 }
 
@@ -125,16 +146,19 @@ enum Op {
     Sq,
 }
 
-impl From<&str> for Op {
-    fn from(value: &str) -> Self {
+impl<'a> TryFrom<&'a str> for Op {
+    type Error = ParseError<'a>;
+
+    /// Parses the right hand side of an operation after `new = old`, such
+    /// as `* 19` or `* old`.
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
         let value = value.trim();
-        let mut op_vals = value.split_ascii_whitespace().rev().take(2);
-        match op_vals.next().unwrap().parse() {
-            Err(_) => Op::Sq,
-            Ok(num) => match op_vals.next().unwrap() {
-                "+" => Op::Add(num),
-                _ => Op::Mul(num),
-            },
+        match value.split_once(' ') {
+            Some(("*", "old")) => Ok(Op::Sq),
+            Some(("+", "old")) => Ok(Op::Mul(2)),
+            Some(("+", num)) => Ok(Op::Add(parse_number(num, "a number or `old`")?)),
+            Some(("*", num)) => Ok(Op::Mul(parse_number(num, "a number or `old`")?)),
+            _ => Err(ParseError::new(value, "expected `+` or `*` and an operand")),
         }
     }
 }
@@ -152,24 +176,15 @@ impl Op {
 #[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-    use test::Bencher;
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) {
-        let input = util::read_input("inputs/day11.txt").unwrap();
-        b.iter(|| {
-            let mut monkeys = parse(input.clone());
-            part_1(&mut monkeys);
-        })
-    }
-
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) {
-        let input = util::read_input("inputs/day11.txt").unwrap();
-        b.iter(|| {
-            let mut monkeys = parse(input.clone());
-            part_2(&mut monkeys);
-        })
+    use crate::days::check_example;
+
+    #[test]
+    fn test_example() {
+        check_example(
+            &Day11,
+            include_str!("examples/day11.txt"),
+            10197,
+            2713310158u64,
+        );
     }
 }
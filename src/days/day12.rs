@@ -1,47 +1,78 @@
-use crate::util;
+use super::Solution;
+use crate::error::ParseError;
 use std::collections::{HashSet, VecDeque};
 
 const DIRECTIONS: [(isize, isize); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
 
-pub fn run() {
-    let raw_input = util::read_input("inputs/day12.txt").unwrap();
-    let grid = parse(raw_input);
-    println!(
-        "part 1: {}",
-        grid.find_shortest_path_length_by(|pos| pos == grid.start)
-    );
-    println!(
-        "part 2: {}",
-        grid.find_shortest_path_length_by(|pos| grid.map[pos.0][pos.1] == 0)
-    );
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Grid;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        parse(input)
+    }
+
+    fn part_1(&self, input: &Self::Input) -> usize {
+        input.find_shortest_path_length_by(|pos| pos == input.start)
+    }
+
+    fn part_2(&self, input: &Self::Input) -> usize {
+        input.find_shortest_path_length_by(|pos| input.map[pos.0][pos.1] == 0)
+    }
 }
 
-fn parse(input: String) -> Grid {
+fn parse(input: &str) -> Result<Grid, ParseError<'_>> {
     let mut grid = Grid::new();
-    grid.map = input
-        .lines()
-        .enumerate()
-        .map(|(row, line)| {
-            line.char_indices()
-                .map(|(col, c)| match c {
-                    'S' => {
-                        grid.start = (row, col);
-                        0
-                    }
-                    'E' => {
-                        grid.end = (row, col);
-                        25
-                    }
-                    h => ((h as u32 - 71) % 26) as i64,
-                })
-                .collect()
-        })
-        .collect();
-    grid
+    let (mut found_start, mut found_end) = (false, false);
+    for (row, line) in input.lines().enumerate() {
+        let mut heights = Vec::new();
+        for (col, c) in line.char_indices() {
+            heights.push(match c {
+                'S' => {
+                    grid.start = (row, col);
+                    found_start = true;
+                    0
+                }
+                'E' => {
+                    grid.end = (row, col);
+                    found_end = true;
+                    25
+                }
+                'a'..='z' => ((c as u32 - 71) % 26) as i64,
+                _ => {
+                    return Err(ParseError::new(
+                        &line[col..col + c.len_utf8()],
+                        "expected a height from a to z, S or E",
+                    ))
+                }
+            });
+        }
+        if grid
+            .map
+            .first()
+            .is_some_and(|first| first.len() != heights.len())
+        {
+            return Err(ParseError::new(line, "expected rows of equal length"));
+        }
+        grid.map.push(heights);
+    }
+    if !found_start {
+        return Err(ParseError::after(input, "expected a start position `S`"));
+    }
+    if !found_end {
+        return Err(ParseError::after(
+            input,
+            "expected a best signal position `E`",
+        ));
+    }
+    Ok(grid)
 }
 
 #[derive(Debug, Clone)]
-struct Grid {
+pub struct Grid {
     map: Vec<Vec<i64>>,
     start: (usize, usize),
     end: (usize, usize),
@@ -102,41 +133,25 @@ impl Grid {
 #[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-    use test::Bencher;
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) {
-        let input = util::read_input("inputs/day12.txt").unwrap();
-        b.iter(|| {
-            let grid = parse(input.clone());
-            grid.find_shortest_path_length_by(|pos| pos == grid.start);
-        })
-    }
-
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) {
-        let input = util::read_input("inputs/day12.txt").unwrap();
-        b.iter(|| {
-            let grid = parse(input.clone());
-            grid.find_shortest_path_length_by(|pos| grid.map[pos.0][pos.1] == 0);
-        })
-    }
+    use crate::days::check_example;
 
     #[test]
     fn test_part_1() {
-        let input = String::from(
-            "Sabqponm
+        let input = "Sabqponm
 abcryxxl
 accszExk
 acctuvwj
-abdefghi",
-        );
-        let grid = parse(input);
+abdefghi";
+        let grid = parse(input).unwrap();
 
         assert_eq!(
             31,
             grid.find_shortest_path_length_by(|pos| pos == grid.start)
         );
     }
+
+    #[test]
+    fn test_example() {
+        check_example(&Day12, include_str!("examples/day12.txt"), 31, 29);
+    }
 }
@@ -1,34 +1,43 @@
-use crate::util;
+use super::Solution;
+use crate::error::{parse_number, ParseError};
 use std::cmp::Ordering;
-use std::error::Error;
 use std::iter::zip;
 
-pub fn run() -> Result<(), Box<dyn Error>> {
-    let raw_input = util::read_input("inputs/day13.txt")?;
-    let pairs = parse(raw_input.clone())?;
-    println!("part 1: {}", part_1(&pairs));
-    let packets = parse_p2(raw_input.clone())?;
-    println!("part 2: {}", part_2(packets));
-    Ok(())
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = Vec<Pair>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        parse(input)
+    }
+
+    fn part_1(&self, input: &Self::Input) -> usize {
+        part_1(input)
+    }
+
+    fn part_2(&self, input: &Self::Input) -> usize {
+        let packets = input
+            .iter()
+            .flat_map(|(a, b)| [a.clone(), b.clone()])
+            .collect();
+        part_2(packets)
+    }
 }
 
-fn parse(input: String) -> Result<Vec<Pair>, ParsePacketError> {
-    Ok(input
+fn parse(input: &str) -> Result<Vec<Pair>, ParseError<'_>> {
+    input
         .trim()
         .split("\n\n")
         .map(|pair_str| {
-            let (p1, p2) = pair_str.split_once('\n').unwrap();
-            (p1.try_into().unwrap(), p2.try_into().unwrap())
+            let (p1, p2) = pair_str
+                .split_once('\n')
+                .ok_or_else(|| ParseError::after(pair_str, "expected a second packet"))?;
+            Ok((p1.try_into()?, p2.try_into()?))
         })
-        .collect())
-}
-
-fn parse_p2(input: String) -> Result<Vec<Packet>, ParsePacketError> {
-    Ok(input
-        .lines()
-        .filter(|line| !line.is_empty())
-        .map(|packet_str| packet_str.try_into().unwrap())
-        .collect())
+        .collect()
 }
 
 fn part_1(pairs: &[Pair]) -> usize {
@@ -62,14 +71,14 @@ fn part_2(packets: Vec<Packet>) -> usize {
 type Pair = (Packet, Packet);
 
 #[derive(Debug, Clone)]
-enum Packet {
+pub enum Packet {
     Num(u16),
     List(Vec<Packet>),
 }
 
-impl TryFrom<&str> for Packet {
-    type Error = ParsePacketError;
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
+impl<'a> TryFrom<&'a str> for Packet {
+    type Error = ParseError<'a>;
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
         Packet::parse(value)
     }
 }
@@ -96,131 +105,94 @@ impl PartialEq for Packet {
 }
 
 impl Packet {
-    fn parse(s: &str) -> Result<Self, ParsePacketError> {
-        let packet_chars: Vec<_> = s.chars().collect();
-
-        let (packet, remaining) = Self::parse_list(&packet_chars);
+    fn parse(s: &str) -> Result<Self, ParseError<'_>> {
+        if !s.starts_with('[') {
+            return Err(ParseError::new(s, "expected a packet starting with `[`"));
+        }
+        let (packet, remaining) = Self::parse_list(s)?;
         if !remaining.is_empty() {
-            let remaining: String = remaining.iter().collect();
-            return Err(ParsePacketError::new(&remaining));
+            return Err(ParseError::new(remaining, "unexpected data after packet"));
         }
         Ok(packet)
     }
 
-    fn parse_list(chars: &[char]) -> (Packet, &[char]) {
-        let mut list_contents = &chars[1..];
+    fn parse_list(s: &str) -> Result<(Packet, &str), ParseError<'_>> {
+        let mut list_contents = &s[1..];
         let mut packets = Vec::new();
 
         loop {
-            match list_contents[0] {
-                ']' => break, // reached the end of the list
-                ',' => list_contents = &list_contents[1..],
-                '[' => {
-                    let (packet, remaining) = Packet::parse_list(list_contents);
+            match list_contents.chars().next() {
+                Some(']') => break, // reached the end of the list
+                Some(',') => list_contents = &list_contents[1..],
+                Some('[') => {
+                    let (packet, remaining) = Packet::parse_list(list_contents)?;
                     packets.push(packet);
                     list_contents = remaining;
                 }
-                _ => {
-                    let (num, remaining) = Packet::parse_num(list_contents);
+                Some(c) if c.is_ascii_digit() => {
+                    let (num, remaining) = Packet::parse_num(list_contents)?;
                     packets.push(num);
                     list_contents = remaining;
                 }
+                Some(c) => {
+                    return Err(ParseError::new(
+                        &list_contents[..c.len_utf8()],
+                        "expected a number, `[` or `]`",
+                    ))
+                }
+                None => return Err(ParseError::new(list_contents, "expected `]`")),
             }
         }
 
-        (Packet::List(packets), &list_contents[1..])
+        Ok((Packet::List(packets), &list_contents[1..]))
     }
 
-    fn parse_num(chars: &[char]) -> (Packet, &[char]) {
-        let mut i = 0;
-        while i < chars.len() && chars[i].is_ascii_digit() {
-            i += 1;
-        }
-        let num = chars[0..i].iter().collect::<String>().parse().unwrap();
-        (Packet::Num(num), &chars[i..])
+    fn parse_num(s: &str) -> Result<(Packet, &str), ParseError<'_>> {
+        let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let num = parse_number(&s[..end], "a number")?;
+        Ok((Packet::Num(num), &s[end..]))
     }
 
     fn in_order(&self, other: &Packet) -> Option<bool> {
         match (self, other) {
-            (Packet::Num(x), Packet::Num(y)) => {
-                if x < y {
-                    return Some(true);
-                } else if x > y {
-                    return Some(false);
-                } else {
-                    return None;
-                }
-            }
+            (Packet::Num(x), Packet::Num(y)) => match x.cmp(y) {
+                Ordering::Less => Some(true),
+                Ordering::Greater => Some(false),
+                Ordering::Equal => None,
+            },
             (Packet::List(l1), Packet::List(l2)) => {
                 for (a, b) in zip(l1, l2) {
                     if a.in_order(b).is_some() {
                         return a.in_order(b);
                     }
                 }
-                if l1.len() < l2.len() {
-                    return Some(true);
-                } else if l1.len() > l2.len() {
-                    return Some(false);
-                } else {
-                    return None;
+                match l1.len().cmp(&l2.len()) {
+                    Ordering::Less => Some(true),
+                    Ordering::Greater => Some(false),
+                    Ordering::Equal => None,
                 }
             }
             (Packet::Num(val), x) => {
                 let new_packet = vec![Packet::Num(*val)];
                 let new_list_packet = Packet::List(new_packet);
-                return new_list_packet.in_order(x);
+                new_list_packet.in_order(x)
             }
             (x, Packet::Num(val)) => {
                 let new_packet = vec![Packet::Num(*val)];
                 let new_list_packet = Packet::List(new_packet);
-                return x.in_order(&new_list_packet);
+                x.in_order(&new_list_packet)
             }
         }
     }
 }
 
-#[derive(Debug, Clone)]
-struct ParsePacketError {
-    data: String,
-}
-
-impl std::error::Error for ParsePacketError {}
-
-impl std::fmt::Display for ParsePacketError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "unable to parse data as packet: {}", self.data)
-    }
-}
-
-impl ParsePacketError {
-    fn new(packet_str: &str) -> Self {
-        Self {
-            data: packet_str.to_owned(),
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-    use test::Bencher;
-
-    #[bench]
-    fn bench_part_1(b: &mut Bencher) {
-        let input = util::read_input("inputs/day13.txt").unwrap();
-        b.iter(|| {
-            let pairs = parse(input.clone()).unwrap();
-            part_1(&pairs);
-        })
-    }
+    use crate::days::check_example;
 
-    #[bench]
-    fn bench_part_2(b: &mut Bencher) {
-        let input = util::read_input("inputs/day13.txt").unwrap();
-        b.iter(|| {
-            let packets = parse_p2(input.clone()).unwrap();
-            part_2(packets);
-        })
+    #[test]
+    fn test_example() {
+        check_example(&Day13, include_str!("examples/day13.txt"), 13, 140);
     }
 }
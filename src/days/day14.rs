@@ -1,30 +1,61 @@
 use itertools::Itertools;
 
-use crate::util;
+use super::Solution;
+use crate::error::{parse_number, ParseError};
 use std::{collections::HashSet, hash::Hash};
 
 const STARTING_POINT: Point = Point { x: 500, y: 0 };
 
-pub fn run() {
-    let raw_input = util::read_input("inputs/day14.txt").unwrap();
-    let mut cave = Cave::from(raw_input.as_str());
-    while cave.tick().is_some() {}
-    println!("{}", cave.fallen_sand.len());
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Cave;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        Cave::try_from(input)
+    }
+
+    fn part_1(&self, input: &Self::Input) -> usize {
+        let mut cave = input.clone();
+        while cave.tick().is_some() {
+            // Without the floor, sand below the lowest rock falls forever.
+            if cave
+                .curr_sand
+                .as_ref()
+                .is_some_and(|sand| sand.y > cave.lowest_point)
+            {
+                break;
+            }
+        }
+        cave.fallen_sand.len()
+    }
+
+    fn part_2(&self, input: &Self::Input) -> usize {
+        let mut cave = input.clone();
+        while cave.tick().is_some() {}
+        cave.fallen_sand.len()
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct Point {
     x: usize,
     y: usize,
 }
 
-impl From<&str> for Point {
-    fn from(value: &str) -> Self {
-        let (x, y) = value.split_once(',').unwrap();
-        Point {
-            x: x.parse().unwrap(),
-            y: y.parse().unwrap(),
-        }
+impl<'a> TryFrom<&'a str> for Point {
+    type Error = ParseError<'a>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let (x, y) = value
+            .split_once(',')
+            .ok_or_else(|| ParseError::new(value, "expected a point like `498,4`"))?;
+        Ok(Point {
+            x: parse_number(x, "a coordinate")?,
+            y: parse_number(y, "a coordinate")?,
+        })
     }
 }
 
@@ -33,13 +64,15 @@ struct RockStructure {
     obstacles: HashSet<Point>,
 }
 
-impl From<&str> for RockStructure {
-    fn from(value: &str) -> Self {
+impl<'a> TryFrom<&'a str> for RockStructure {
+    type Error = ParseError<'a>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
         let points: Vec<Point> = value
             .split("->")
             .map(|point| point.trim())
-            .map(Point::from)
-            .collect();
+            .map(Point::try_from)
+            .collect::<Result<_, _>>()?;
         let mut obstacles = HashSet::new();
         for (curr, next) in points.iter().tuple_windows() {
             if curr.x == next.x {
@@ -64,37 +97,40 @@ impl From<&str> for RockStructure {
                 }
             }
         }
-        RockStructure { obstacles }
+        Ok(RockStructure { obstacles })
     }
 }
 
-#[derive(Debug)]
-struct Cave {
+#[derive(Debug, Clone)]
+pub struct Cave {
     obstacles: HashSet<Point>,
     fallen_sand: HashSet<Point>,
     curr_sand: Option<Point>,
     lowest_point: usize,
 }
 
-impl From<&str> for Cave {
-    fn from(value: &str) -> Self {
+impl<'a> TryFrom<&'a str> for Cave {
+    type Error = ParseError<'a>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
         let mut obstacles = HashSet::new();
-        value
-            .lines()
-            .map(|line| line.trim())
-            .map(RockStructure::from)
-            .for_each(|structure| {
-                obstacles.extend(structure.obstacles);
-            });
+        for line in value.lines() {
+            let structure = RockStructure::try_from(line.trim())?;
+            obstacles.extend(structure.obstacles);
+        }
 
-        let lowest_point = obstacles.iter().max_by(|a, b| a.y.cmp(&b.y)).unwrap().y;
+        let lowest_point = obstacles
+            .iter()
+            .max_by(|a, b| a.y.cmp(&b.y))
+            .ok_or_else(|| ParseError::after(value, "expected at least one path of rock"))?
+            .y;
         // add floor
-        Cave {
+        Ok(Cave {
             obstacles,
             fallen_sand: HashSet::new(),
             curr_sand: None,
             lowest_point,
-        }
+        })
     }
 }
 
@@ -171,29 +207,23 @@ impl Cave {
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
-    extern crate test;
-    use test::Bencher;
+    use crate::days::check_example;
 
-    const TEST_OBSTACLES: &str = "498,4 -> 498,6 -> 496,6
-503,4 -> 502,4 -> 502,9 -> 494,9";
+    const TEST_OBSTACLES: &str = include_str!("examples/day14.txt");
 
     #[test]
     fn test_cave() {
         let input = String::from(TEST_OBSTACLES);
-        let mut cave = Cave::from(input.as_str());
+        let mut cave = Cave::try_from(input.as_str()).unwrap();
         while cave.tick().is_some() {}
         assert_eq!(cave.fallen_sand.len(), 93);
     }
 
-    // #[bench]
-    // fn bench_part_1(b: &mut Bencher) {
-    //     let raw_input = util::read_input("inputs/day14.txt").unwrap();
-    //     b.iter(|| {
-    //         let mut cave = parse(raw_input.clone());
-    //         while cave.tick().is_some() {}
-    //     })
-
-    // }
+    #[test]
+    fn test_example() {
+        check_example(&Day14, TEST_OBSTACLES, 24, 93);
+    }
 }
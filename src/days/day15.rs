@@ -1,16 +1,45 @@
-use crate::util;
-
-pub fn run() {
-    let raw_input = util::read_input("inputs/day15.txt").unwrap();
-    let sensors_and_beacons = parse(raw_input);
-    let manhattans = calculate_manhattans(sensors_and_beacons);
-    let row_with_distress = find_row_with_distress(&manhattans, 4000000).unwrap();
-    let tuning_freq = find_tuning_freq(row_with_distress);
-    println!("part 2: {}", tuning_freq);
+use super::Solution;
+use crate::answer::Answer;
+use crate::error::{parse_number, ParseError};
+use std::collections::HashSet;
+
+/// The puzzle asks about a different row and search area for the example
+/// than for the real input.
+pub struct Day15 {
+    row: i64,
+    search_max: usize,
+}
+
+impl Day15 {
+    pub const fn new(row: i64, search_max: usize) -> Self {
+        Self { row, search_max }
+    }
+}
+
+impl Solution for Day15 {
+    type Input = Vec<(SensorAndBeacon, usize)>;
+    type Part1 = i64;
+    type Part2 = Answer;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>> {
+        Ok(calculate_manhattans(parse(input)?))
+    }
+
+    fn part_1(&self, input: &Self::Input) -> i64 {
+        count_covered(input, self.row)
+    }
+
+    /// Unsolved if the sensors leave no gap in the search area.
+    fn part_2(&self, input: &Self::Input) -> Answer {
+        match find_row_with_distress(input, self.search_max) {
+            Some(row_with_distress) => find_tuning_freq(row_with_distress).into(),
+            None => Answer::Unsolved,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-struct Position {
+pub struct Position {
     x: i64,
     y: i64,
 }
@@ -23,32 +52,42 @@ impl Position {
     }
 }
 
-impl From<&str> for Position {
-    fn from(value: &str) -> Self {
+impl<'a> TryFrom<&'a str> for Position {
+    type Error = ParseError<'a>;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
         let value = value.trim();
-        let (x, y) = value.split_once(',').unwrap();
-        let (_, x) = x.split_once('=').unwrap();
-        let (_, y) = y.split_once('=').unwrap();
-        Position {
-            x: x.parse().unwrap(),
-            y: y.parse().unwrap(),
-        }
+        let invalid = || ParseError::new(value, "expected a position like `x=2, y=18`");
+        let (x, y) = value.split_once(',').ok_or_else(invalid)?;
+        let (_, x) = x.split_once('=').ok_or_else(invalid)?;
+        let (_, y) = y.split_once('=').ok_or_else(invalid)?;
+        Ok(Position {
+            x: parse_number(x, "a coordinate")?,
+            y: parse_number(y, "a coordinate")?,
+        })
     }
 }
 
 type SensorAndBeacon = (Position, Position);
 
-fn parse(input: String) -> Vec<SensorAndBeacon> {
+fn parse(input: &str) -> Result<Vec<SensorAndBeacon>, ParseError<'_>> {
     input
         .lines()
         .map(|line| {
-            let (sensor_str, beacon_str) = line.split_once(':').unwrap();
-            let sensor_str = sensor_str.strip_prefix("Sensor at").unwrap();
+            let (sensor_str, beacon_str) = line
+                .split_once(':')
+                .ok_or_else(|| ParseError::new(line, "expected a sensor and a beacon"))?;
+            let sensor_str = sensor_str
+                .strip_prefix("Sensor at")
+                .ok_or_else(|| ParseError::new(sensor_str, "expected `Sensor at`"))?;
+            let beacon_str = beacon_str.trim();
             let beacon_str = beacon_str
-                .trim()
                 .strip_prefix("closest beacon is at")
-                .unwrap();
-            (Position::from(sensor_str), Position::from(beacon_str))
+                .ok_or_else(|| ParseError::new(beacon_str, "expected `closest beacon is at`"))?;
+            Ok((
+                Position::try_from(sensor_str)?,
+                Position::try_from(beacon_str)?,
+            ))
         })
         .collect()
 }
@@ -72,7 +111,7 @@ fn get_row_segment_at_manhattan(
     dis: usize,
     row: i64,
 ) -> Option<(i64, i64)> {
-    let (sensor, beacon) = sensor_and_beacon;
+    let (sensor, _) = sensor_and_beacon;
     // check if shortest path to row is more than distance away
     let y_diff: usize = sensor.manhattan_distance(&Position {
         x: sensor.x,
@@ -101,6 +140,21 @@ fn get_row_segments(sensors_and_beacons: &[(SensorAndBeacon, usize)], row: i64)
     merge_segments(segments)
 }
 
+/// Positions in `row` where a beacon cannot be.
+fn count_covered(sensors_and_beacons: &[(SensorAndBeacon, usize)], row: i64) -> i64 {
+    let segments = get_row_segments(sensors_and_beacons, row);
+    let beacons_in_row: HashSet<Position> = sensors_and_beacons
+        .iter()
+        .map(|((_, beacon), _)| *beacon)
+        .filter(|beacon| beacon.y == row)
+        .collect();
+    let covered: i64 = segments
+        .iter()
+        .map(|(min_x, max_x)| max_x - min_x + 1)
+        .sum();
+    covered - beacons_in_row.len() as i64
+}
+
 fn merge_segments(mut input: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
     let mut merged = Vec::new();
     if input.is_empty() {
@@ -145,35 +199,30 @@ fn find_tuning_freq(input: (Vec<(i64,i64)>, usize)) -> i64 {
     (x_ranges[0].1 + 1) * 4000000 + row as i64
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
+    use crate::days::check_example;
 
-    const TEST_INPUT: &str = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
-Sensor at x=9, y=16: closest beacon is at x=10, y=16
-Sensor at x=13, y=2: closest beacon is at x=15, y=3
-Sensor at x=12, y=14: closest beacon is at x=10, y=16
-Sensor at x=10, y=20: closest beacon is at x=10, y=16
-Sensor at x=14, y=17: closest beacon is at x=10, y=16
-Sensor at x=8, y=7: closest beacon is at x=2, y=10
-Sensor at x=2, y=0: closest beacon is at x=2, y=10
-Sensor at x=0, y=11: closest beacon is at x=2, y=10
-Sensor at x=20, y=14: closest beacon is at x=25, y=17
-Sensor at x=17, y=20: closest beacon is at x=21, y=22
-Sensor at x=16, y=7: closest beacon is at x=15, y=3
-Sensor at x=14, y=3: closest beacon is at x=15, y=3
-Sensor at x=20, y=1: closest beacon is at x=15, y=3";
-
-    // #[test]
-    // fn test_part_1() {
-    //     let sensors_and_beacons = parse(String::from(TEST_INPUT));
-    //     let manhattans = calculate_manhattans(sensors_and_beacons);
-    //     let segments = get_row_segments(&manhattans, 10);
-    //     assert_eq!(segments.len(), 26);
-    // }
+    const TEST_INPUT: &str = include_str!("examples/day15.txt");
+
+    #[test]
+    fn test_example() {
+        check_example(&Day15::new(10, 20), TEST_INPUT, 26, 56000011);
+    }
+
+    #[test]
+    fn test_no_gap() {
+        let day = Day15::new(10, 20);
+        let input = day
+            .parse("Sensor at x=10, y=10: closest beacon is at x=30, y=10")
+            .unwrap();
+        assert_eq!(day.part_2(&input), Answer::Unsolved);
+    }
 
     #[test]
     fn test_part_2() {
-        let sensors_and_beacons = parse(String::from(TEST_INPUT));
+        let sensors_and_beacons = parse(TEST_INPUT).unwrap();
         let manhattans = calculate_manhattans(sensors_and_beacons);
         let row_with_distress = find_row_with_distress(&manhattans, 20).unwrap();
         let tuning_freq = find_tuning_freq(row_with_distress);
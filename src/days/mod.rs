@@ -1,48 +1,232 @@
+use crate::answer::Answer;
+use crate::error::{self, ParseError};
+use serde::{Serialize, Serializer};
+use std::any::Any;
+use std::str::FromStr;
+
 mod day01;
 mod day02;
 mod day03;
 mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
 mod day11;
 mod day12;
 mod day13;
 mod day14;
 mod day15;
 
-pub fn run() {
-    println!("Day 01 ***********");
-    day01::run();
-    println!("Day 02 ***********");
-    day02::run();
-    println!("Day 03 ***********");
-    day03::run();
-    println!("Day 04 ***********");
-    day04::run();
-    println!("Day 05 ***********");
-    day05::run();
-    println!("Day 06 ***********");
-    day06::run();
-    println!("Day 07 ***********");
-    day07::run();
-    println!("Day 08 ***********");
-    day08::run();
-    println!("Day 09 ***********");
-    day09::run();
-    println!("Day 10 ***********");
-    day10::run();
-    println!("Day 11 ***********");
-    day11::run();
-    println!("Day 12 ***********");
-    day12::run();
-    println!("Day 13 ***********");
-    day13::run().unwrap();
-    // println!("Day 14 ***********");
-    // day14::run();
-    println!("Day 15 ***********");
-    day15::run();
+/// A single day's puzzle. The input is parsed once and shared by both parts.
+pub trait Solution {
+    type Input;
+    type Part1: Into<Answer>;
+    type Part2: Into<Answer>;
+
+    fn parse<'a>(&self, input: &'a str) -> Result<Self::Input, ParseError<'a>>;
+    fn part_1(&self, input: &Self::Input) -> Self::Part1;
+    fn part_2(&self, input: &Self::Input) -> Self::Part2;
+}
+
+/// Parsed input of a type-erased [`Solution`].
+pub struct Parsed(Box<dyn Any + Send + Sync>);
+
+/// Object safe view of a [`Solution`], used by the registry so that days with
+/// different input and answer types can be iterated over together.
+pub trait DynSolution: Sync {
+    fn parse<'a>(&self, input: &'a str) -> Result<Parsed, ParseError<'a>>;
+    fn solve(&self, input: &Parsed, part: Part) -> Answer;
+}
+
+impl<S> DynSolution for S
+where
+    S: Solution + Sync,
+    S::Input: Send + Sync + 'static,
+{
+    fn parse<'a>(&self, input: &'a str) -> Result<Parsed, ParseError<'a>> {
+        let input = Solution::parse(self, input)?;
+        Ok(Parsed(Box::new(input)))
+    }
+
+    fn solve(&self, input: &Parsed, part: Part) -> Answer {
+        let input = input
+            .0
+            .downcast_ref::<S::Input>()
+            .expect("input was parsed by a different solution");
+        match part {
+            Part::One => self.part_1(input).into(),
+            Part::Two => self.part_2(input).into(),
+        }
+    }
+}
+
+pub struct Day {
+    pub number: u8,
+    pub solution: &'static dyn DynSolution,
+}
+
+impl Day {
+    const fn new(number: u8, solution: &'static dyn DynSolution) -> Self {
+        Self { number, solution }
+    }
+
+    /// Parses this day's puzzle input, reporting where in the input any
+    /// problem was found.
+    pub fn parse(&self, input: &str) -> error::Result<Parsed> {
+        self.solution
+            .parse(input)
+            .map_err(|err| err.locate(self.number, input))
+    }
+}
+
+/// Every day that currently has a solution, in order.
+pub static REGISTRY: [Day; 15] = [
+    Day::new(1, &day01::Day01),
+    Day::new(2, &day02::Day02),
+    Day::new(3, &day03::Day03),
+    Day::new(4, &day04::Day04),
+    Day::new(5, &day05::Day05),
+    Day::new(6, &day06::Day06),
+    Day::new(7, &day07::Day07),
+    Day::new(8, &day08::Day08),
+    Day::new(9, &day09::Day09),
+    Day::new(10, &day10::Day10),
+    Day::new(11, &day11::Day11),
+    Day::new(12, &day12::Day12),
+    Day::new(13, &day13::Day13),
+    Day::new(14, &day14::Day14),
+    Day::new(15, &day15::Day15::new(2_000_000, 4_000_000)),
+];
+
+pub fn get(day: u8) -> Option<&'static Day> {
+    REGISTRY.iter().find(|entry| entry.number == day)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const BOTH: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(&self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl Serialize for Part {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.number())
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            _ => Err(format!("invalid part `{}`, expected 1 or 2", s)),
+        }
+    }
+}
+
+/// A set of days such as `12`, `3..7` (inclusive), `3..=7` or `1,4,9..11`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaySelection(Vec<u8>);
+
+impl DaySelection {
+    pub fn all() -> Self {
+        DaySelection(REGISTRY.iter().map(|day| day.number).collect())
+    }
+
+    pub fn days(&self) -> impl Iterator<Item = &'static Day> + '_ {
+        self.0.iter().filter_map(|&day| get(day))
+    }
+}
+
+impl FromStr for DaySelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_days(s).map(DaySelection)
+    }
+}
+
+fn parse_days(spec: &str) -> Result<Vec<u8>, String> {
+    let mut days = Vec::new();
+    for item in spec.split(',').map(str::trim) {
+        let (start, end) = match item.split_once("..") {
+            Some((start, end)) => (start, end.strip_prefix('=').unwrap_or(end)),
+            None => (item, item),
+        };
+        let start = parse_day(start)?;
+        let end = parse_day(end)?;
+        if start > end {
+            return Err(format!("empty day range `{}`", item));
+        }
+        days.extend(start..=end);
+    }
+    days.sort_unstable();
+    days.dedup();
+    Ok(days)
+}
+
+fn parse_day(day: &str) -> Result<u8, String> {
+    let parsed: u8 = day
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid day `{}`", day))?;
+    if get(parsed).is_none() {
+        return Err(format!("no solution for day {}", parsed));
+    }
+    Ok(parsed)
+}
+
+/// Checks a solution's answers to a worked example from the puzzle text.
+#[cfg(test)]
+fn check_example(
+    solution: &dyn DynSolution,
+    input: &str,
+    part_1: impl Into<Answer>,
+    part_2: impl Into<Answer>,
+) {
+    let parsed = match solution.parse(input) {
+        Ok(parsed) => parsed,
+        Err(err) => panic!("{}", err.locate(0, input)),
+    };
+    assert_eq!(solution.solve(&parsed, Part::One), part_1.into(), "part 1");
+    assert_eq!(solution.solve(&parsed, Part::Two), part_2.into(), "part 2");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(parse_days("12"), Ok(vec![12]));
+        assert_eq!(parse_days("3..7"), Ok(vec![3, 4, 5, 6, 7]));
+        assert_eq!(parse_days("3..=5"), Ok(vec![3, 4, 5]));
+        assert_eq!(parse_days("9,1..2,1"), Ok(vec![1, 2, 9]));
+        assert!(parse_days("0").is_err());
+        assert!(parse_days("7..3").is_err());
+        assert!(parse_days("x").is_err());
+    }
+
+    #[test]
+    fn test_registry_is_ordered() {
+        for (i, day) in REGISTRY.iter().enumerate() {
+            assert_eq!(day.number as usize, i + 1);
+        }
+    }
 }
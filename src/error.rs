@@ -0,0 +1,144 @@
+use std::fmt;
+use std::io;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Puzzle input that does not match the expected format.
+    Parse {
+        day: u8,
+        line: usize,
+        column: usize,
+        text: String,
+        reason: String,
+    },
+    /// A puzzle input or other file that could not be read.
+    Io { path: String, source: io::Error },
+    /// An expected answers file that is not in the expected format.
+    AnswerFile { path: String, reason: String },
+    /// A saved benchmark baseline that is not in the expected format.
+    Baseline { path: String, reason: String },
+    /// A file that could not be written.
+    Write { path: String, source: io::Error },
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } | Error::Write { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse {
+                day,
+                line,
+                column,
+                text,
+                reason,
+            } => {
+                write!(
+                    f,
+                    "day {:02}, line {}, column {}: {}",
+                    day, line, column, reason
+                )?;
+                if text.is_empty() {
+                    write!(f, ", found end of line")
+                } else {
+                    write!(f, ", found `{}`", text)
+                }
+            }
+            Error::Io { path, source } => write!(f, "unable to read {}: {}", path, source),
+            Error::AnswerFile { path, reason } => {
+                write!(f, "invalid answers file {}: {}", path, reason)
+            }
+            Error::Baseline { path, reason } => {
+                write!(f, "invalid benchmark baseline {}: {}", path, reason)
+            }
+            Error::Write { path, source } => write!(f, "unable to write {}: {}", path, source),
+        }
+    }
+}
+
+/// A parse failure pointing at the offending slice of a puzzle input.
+///
+/// Day parsers only see the input they are given, so they return this and
+/// leave it to the caller to [`locate`](ParseError::locate) the slice within
+/// the whole file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError<'a> {
+    at: &'a str,
+    reason: String,
+}
+
+impl<'a> ParseError<'a> {
+    pub fn new(at: &'a str, reason: impl Into<String>) -> Self {
+        Self {
+            at,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// An error for something missing after the end of `at`.
+    pub fn after(at: &'a str, reason: impl Into<String>) -> Self {
+        Self::new(&at[at.len()..], reason)
+    }
+
+    /// Converts to an [`Error`] with the line and column of the offending
+    /// slice within `input`, which must be the text the slice was taken from.
+    pub fn locate(self, day: u8, input: &str) -> Error {
+        let start = input.as_ptr() as usize;
+        let offset = (self.at.as_ptr() as usize)
+            .checked_sub(start)
+            .filter(|&offset| offset <= input.len())
+            .unwrap_or(0);
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Error::Parse {
+            day,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            text: self.at.to_owned(),
+            reason: self.reason,
+        }
+    }
+}
+
+/// Parses a number, reporting `what` was expected on failure.
+pub fn parse_number<'a, T: std::str::FromStr>(
+    at: &'a str,
+    what: &str,
+) -> std::result::Result<T, ParseError<'a>> {
+    at.parse()
+        .map_err(|_| ParseError::new(at, format!("expected {}", what)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate() {
+        let input = "1-2,3-4\n5-6,x-8\n";
+        let at = &input[12..13];
+        match ParseError::new(at, "expected a section").locate(4, input) {
+            Error::Parse {
+                day,
+                line,
+                column,
+                text,
+                ..
+            } => assert_eq!((day, line, column, text.as_str()), (4, 2, 5, "x")),
+            err => panic!("unexpected error {}", err),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::error::{Error, Result};
+use crate::util;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Environment variable naming the directory that holds the puzzle inputs.
+pub const INPUTS_DIR_ENV: &str = "AOC_INPUTS";
+
+const DEFAULT_INPUTS_DIR: &str = "inputs";
+
+/// Where to read a single day's puzzle input from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    File(PathBuf),
+    Stdin,
+}
+
+impl FromStr for InputSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "" => Err(String::from("empty input path")),
+            "-" => Ok(InputSource::Stdin),
+            path => Ok(InputSource::File(PathBuf::from(path))),
+        }
+    }
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::File(path) => write!(f, "{}", path.display()),
+            InputSource::Stdin => write!(f, "<stdin>"),
+        }
+    }
+}
+
+/// Locates the puzzle input for each day: `dayNN.txt` inside an inputs
+/// directory, unless the day has been given a file of its own.
+#[derive(Debug, Clone)]
+pub struct Inputs {
+    dir: PathBuf,
+    overrides: HashMap<u8, InputSource>,
+}
+
+impl Inputs {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Uses the directory named by `AOC_INPUTS`, falling back to `inputs`.
+    pub fn from_env() -> Self {
+        match std::env::var_os(INPUTS_DIR_ENV) {
+            Some(dir) if !dir.is_empty() => Self::new(dir),
+            _ => Self::new(DEFAULT_INPUTS_DIR),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn with_override(mut self, day: u8, source: InputSource) -> Self {
+        self.overrides.insert(day, source);
+        self
+    }
+
+    pub fn source(&self, day: u8) -> InputSource {
+        match self.overrides.get(&day) {
+            Some(source) => source.clone(),
+            None => InputSource::File(self.dir.join(format!("day{:02}.txt", day))),
+        }
+    }
+
+    pub fn read(&self, day: u8) -> Result<String> {
+        match self.source(day) {
+            InputSource::File(path) => util::read_input(&path),
+            InputSource::Stdin => util::read_stdin().map_err(|source| Error::Io {
+                path: InputSource::Stdin.to_string(),
+                source,
+            }),
+        }
+    }
+}
+
+impl Default for Inputs {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source() {
+        let inputs = Inputs::new("accounts/alice").with_override(7, "-".parse().unwrap());
+        assert_eq!(
+            inputs.source(3),
+            InputSource::File(PathBuf::from("accounts/alice/day03.txt"))
+        );
+        assert_eq!(inputs.source(7), InputSource::Stdin);
+    }
+}
@@ -1,3 +1,9 @@
-#![feature(test)]
+pub mod answer;
+pub mod bench;
 pub mod days;
+pub mod error;
+pub mod inputs;
+pub mod ocr;
+pub mod runner;
 mod util;
+pub mod verify;
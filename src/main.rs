@@ -1,5 +1,563 @@
-use aoc_2022::days;
+use aoc_2022::bench::{self, Baseline};
+use aoc_2022::days::day07::{self, FileSystem};
+use aoc_2022::days::day08::{self, Heatmap};
+use aoc_2022::days::day09::{self, Dimensions, Rope, Trace};
+use aoc_2022::days::day10::{
+    self, Command as DebugCommand, Cpu, Crt, Debugger, SignalSampler, Tracer,
+};
+use aoc_2022::days::{self, DaySelection, Part};
+use aoc_2022::error::{Error, Result};
+use aoc_2022::inputs::{InputSource, Inputs};
+use aoc_2022::ocr;
+use aoc_2022::runner::{self, Format, Options};
+use aoc_2022::verify::{self, AnswerFile};
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
 
-fn main() {
-    days::run();
+#[derive(Parser)]
+#[command(version, about = "Advent of Code 2022 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    inputs: InputArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the solutions for one or more days
+    Run(RunArgs),
+    /// Check answers against a file of expected answers
+    Verify(VerifyArgs),
+    /// Benchmark parsing and solving, optionally against a saved baseline
+    Bench(BenchArgs),
+    /// Print the filesystem rebuilt from the day 7 terminal log
+    Fs(FsArgs),
+    /// Draw a heatmap of which day 8 trees are visible, or of their scenic scores
+    Trees(TreesArgs),
+    /// Animate the day 9 rope, or draw the positions its tail visited
+    Rope(RopeArgs),
+    /// Run the day 10 program on the CPU emulator and summarise what it did
+    Cpu(CpuArgs),
+}
+
+#[derive(Args)]
+struct InputArgs {
+    /// Directory holding the `dayNN.txt` puzzle inputs [env: AOC_INPUTS] [default: inputs]
+    #[arg(long, global = true, value_name = "DIR")]
+    inputs: Option<PathBuf>,
+
+    /// Read a day's input from PATH instead, or from stdin if PATH is `-`.
+    /// The day may be left out when only one day is selected
+    #[arg(long = "input", global = true, value_name = "[DAY=]PATH", value_parser = parse_input_override)]
+    overrides: Vec<(Option<u8>, InputSource)>,
+}
+
+#[derive(Args)]
+struct SelectionArgs {
+    /// Days to run, e.g. `12`, `3..7` (inclusive) or `1,4,9`
+    #[arg(required_unless_present = "all")]
+    days: Option<DaySelection>,
+
+    /// Run every day
+    #[arg(long, conflicts_with = "days")]
+    all: bool,
+
+    /// Only run the given part
+    #[arg(short, long)]
+    part: Option<Part>,
+}
+
+impl SelectionArgs {
+    fn days(&self) -> DaySelection {
+        match &self.days {
+            Some(days) if !self.all => days.clone(),
+            _ => DaySelection::all(),
+        }
+    }
+
+    fn parts(&self) -> Vec<Part> {
+        match self.part {
+            Some(part) => vec![part],
+            None => Part::BOTH.to_vec(),
+        }
+    }
+}
+
+#[derive(Args)]
+struct RunArgs {
+    #[command(flatten)]
+    selection: SelectionArgs,
+
+    /// Output format: text, json or csv
+    #[arg(long, default_value = "text")]
+    format: Format,
+
+    /// Parse and solve each day N times, reporting the median and minimum times
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    repeat: u32,
+
+    /// Use up to N threads, solving several days and parts at once, or one per CPU if N is 0
+    #[arg(short, long, value_name = "N", default_value_t = 1)]
+    jobs: usize,
+}
+
+#[derive(Args)]
+struct VerifyArgs {
+    #[command(flatten)]
+    selection: SelectionArgs,
+
+    /// TOML file of expected answers [default: answers.toml in the inputs directory]
+    #[arg(long, value_name = "FILE")]
+    answers: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct BenchArgs {
+    #[command(flatten)]
+    selection: SelectionArgs,
+
+    /// Milliseconds to run each benchmark before measuring
+    #[arg(long, value_name = "MS", default_value_t = 300)]
+    warmup_ms: u64,
+
+    /// Milliseconds to spend measuring each benchmark
+    #[arg(long, value_name = "MS", default_value_t = 1000)]
+    measure_ms: u64,
+
+    /// Number of samples to take of each benchmark
+    #[arg(long, value_name = "N", default_value_t = 50, value_parser = clap::value_parser!(u32).range(1..))]
+    samples: u32,
+
+    /// Compare against a baseline saved with `--save-baseline`
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// Save the results as a baseline for later runs
+    #[arg(long, value_name = "FILE")]
+    save_baseline: Option<PathBuf>,
+
+    /// Percentage slowdown of the median that counts as a regression
+    #[arg(long, value_name = "PCT", default_value_t = 5.0)]
+    threshold: f64,
+}
+
+#[derive(Args)]
+struct FsArgs {
+    /// List directories by total size, like `du -h`, instead of as a tree
+    #[arg(long)]
+    du: bool,
+
+    /// Check the terminal log for inconsistencies instead, reporting each by line number
+    #[arg(long, conflicts_with = "du")]
+    check: bool,
+}
+
+#[derive(Args)]
+struct TreesArgs {
+    /// Draw scenic scores instead of visibility
+    #[arg(long)]
+    scenic: bool,
+
+    /// Save the heatmap as an image instead, greyscale for `.pgm` or in colour for `.ppm`
+    #[arg(long, value_name = "FILE", value_parser = parse_image_path)]
+    image: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct RopeArgs {
+    /// Number of knots in the rope
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u16).range(1..))]
+    knots: u16,
+
+    /// How far each knot may get from the one before it before following. A
+    /// single value applies to every knot, or give one per knot after the head
+    #[arg(
+        long,
+        value_name = "N[,N...]",
+        value_delimiter = ',',
+        default_value = "1"
+    )]
+    slack: Vec<usize>,
+
+    /// Allow moves to leave the plane with F(orward) and B(ack); the rope is drawn as seen from above
+    #[arg(long = "3d")]
+    three_d: bool,
+
+    /// Frames per second of the animation
+    #[arg(long, default_value_t = 10.0, value_parser = parse_fps)]
+    fps: f64,
+
+    /// Follow the head with a view of this many rows and columns, instead of showing the whole area
+    #[arg(long, value_name = "ROWSxCOLS", value_parser = parse_size)]
+    size: Option<(usize, usize)>,
+
+    /// Draw the positions the tail visited instead of animating
+    #[arg(long, conflicts_with_all = ["fps", "size"])]
+    visited: bool,
+}
+
+#[derive(Args)]
+struct CpuArgs {
+    /// Program file to run instead of the day 10 input
+    program: Option<PathBuf>,
+
+    /// Print the CPU state during every cycle
+    #[arg(long)]
+    trace: bool,
+
+    /// Print the program as assembly instead of running it
+    #[arg(long, conflicts_with = "trace")]
+    disassemble: bool,
+
+    /// Stop the program after this many cycles, or with --debug after this many in one `continue`
+    #[arg(long, value_name = "N", default_value_t = day10::DEFAULT_MAX_CYCLES)]
+    max_cycles: usize,
+
+    /// Step through the program interactively; type `help` at the prompt for commands
+    #[arg(long, conflicts_with_all = ["trace", "disassemble"])]
+    debug: bool,
+}
+
+fn parse_fps(s: &str) -> std::result::Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(fps) if fps > 0.0 && fps.is_finite() => Ok(fps),
+        _ => Err("expected a positive number".to_string()),
+    }
+}
+
+fn parse_size(s: &str) -> std::result::Result<(usize, usize), String> {
+    let error = || "expected ROWSxCOLS, e.g. `21x41`".to_string();
+    let (rows, cols) = s.split_once('x').ok_or_else(error)?;
+    match (rows.parse(), cols.parse()) {
+        (Ok(rows @ 1..), Ok(cols @ 1..)) => Ok((rows, cols)),
+        _ => Err(error()),
+    }
+}
+
+fn parse_image_path(s: &str) -> std::result::Result<PathBuf, String> {
+    let path = PathBuf::from(s);
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("pgm" | "ppm") => Ok(path),
+        _ => Err("expected a .pgm or .ppm file".to_string()),
+    }
+}
+
+fn parse_input_override(s: &str) -> std::result::Result<(Option<u8>, InputSource), String> {
+    let Some((day, path)) = s.split_once('=') else {
+        return Ok((None, s.parse()?));
+    };
+    let day: u8 = day.parse().map_err(|_| format!("invalid day `{}`", day))?;
+    if days::get(day).is_none() {
+        return Err(format!("no solution for day {}", day));
+    }
+    Ok((Some(day), path.parse()?))
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Some(Command::Run(args)) => run(args, &cli.inputs),
+        Some(Command::Verify(args)) => verify(args, &cli.inputs),
+        Some(Command::Bench(args)) => bench(args, &cli.inputs),
+        Some(Command::Fs(args)) => fs(args, &cli.inputs),
+        Some(Command::Trees(args)) => trees(args, &cli.inputs),
+        Some(Command::Rope(args)) => rope(args, &cli.inputs),
+        Some(Command::Cpu(args)) => cpu(args, &cli.inputs),
+        None => {
+            let selection = DaySelection::all();
+            let inputs = inputs(&cli.inputs, &selection);
+            runner::run(&selection, &Part::BOTH, &inputs, Options::default())
+                .map(|_| ExitCode::SUCCESS)
+        }
+    };
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Resolves the input arguments, exiting with a usage error if they do not
+/// fit the selected days.
+fn inputs(args: &InputArgs, selection: &DaySelection) -> Inputs {
+    let usage_error = |message: String| -> ! {
+        Cli::command()
+            .error(ErrorKind::ArgumentConflict, message)
+            .exit()
+    };
+
+    let mut inputs = match &args.inputs {
+        Some(dir) => Inputs::new(dir),
+        None => Inputs::from_env(),
+    };
+    let stdin_count = args
+        .overrides
+        .iter()
+        .filter(|(_, source)| *source == InputSource::Stdin)
+        .count();
+    if stdin_count > 1 {
+        usage_error(String::from("only one day can read its input from stdin"));
+    }
+    for (day, source) in &args.overrides {
+        let day = match (day, selection.days().collect::<Vec<_>>().as_slice()) {
+            (Some(day), _) => *day,
+            (None, [only]) => only.number,
+            (None, _) => usage_error(String::from(
+                "`--input` needs a day, e.g. `--input 7=PATH`, when several days are selected",
+            )),
+        };
+        inputs = inputs.with_override(day, source.clone());
+    }
+    inputs
+}
+
+fn run(args: RunArgs, input_args: &InputArgs) -> Result<ExitCode> {
+    let selection = args.selection.days();
+    let inputs = inputs(input_args, &selection);
+    let options = Options {
+        format: args.format,
+        repeat: args.repeat,
+        jobs: match args.jobs {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            jobs => jobs,
+        },
+    };
+    runner::run(&selection, &args.selection.parts(), &inputs, options)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn verify(args: VerifyArgs, input_args: &InputArgs) -> Result<ExitCode> {
+    let selection = args.selection.days();
+    let inputs = inputs(input_args, &selection);
+    let answers_path = match args.answers {
+        Some(path) => path,
+        None => inputs.dir().join("answers.toml"),
+    };
+    let answers = AnswerFile::load(&answers_path)?;
+    let report = verify::verify(&selection, &args.selection.parts(), &inputs, &answers);
+    println!("{}", report);
+    if report.is_success() {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+fn bench(args: BenchArgs, input_args: &InputArgs) -> Result<ExitCode> {
+    let selection = args.selection.days();
+    let inputs = inputs(input_args, &selection);
+    let baseline = args.baseline.as_deref().map(Baseline::load).transpose()?;
+    let config = bench::Config {
+        warmup: Duration::from_millis(args.warmup_ms),
+        measurement: Duration::from_millis(args.measure_ms),
+        samples: args.samples,
+        threshold: args.threshold / 100.0,
+    };
+    let report = bench::run(
+        &selection,
+        &args.selection.parts(),
+        &inputs,
+        &config,
+        baseline.as_ref(),
+    );
+    println!("{}", report);
+    if let Some(path) = args.save_baseline {
+        report.baseline().save(&path)?;
+    }
+    if report.regressions().next().is_some() {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+fn fs(args: FsArgs, input_args: &InputArgs) -> Result<ExitCode> {
+    let selection: DaySelection = "7".parse().expect("day 7 is solved");
+    let raw_input = inputs(input_args, &selection).read(7)?;
+    if args.check {
+        let diagnostics = day07::validate(&raw_input);
+        for diagnostic in &diagnostics {
+            println!("{}", diagnostic);
+        }
+        if !diagnostics.is_empty() {
+            return Ok(ExitCode::FAILURE);
+        }
+        println!("no problems found");
+        return Ok(ExitCode::SUCCESS);
+    }
+    let fs = FileSystem::try_from(raw_input.as_str()).map_err(|err| err.locate(7, &raw_input))?;
+    if args.du {
+        print!("{}", fs.render_du());
+    } else {
+        print!("{}", fs.render_tree());
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn trees(args: TreesArgs, input_args: &InputArgs) -> Result<ExitCode> {
+    let selection: DaySelection = "8".parse().expect("day 8 is solved");
+    let raw_input = inputs(input_args, &selection).read(8)?;
+    let trees = day08::process(&raw_input).map_err(|err| err.locate(8, &raw_input))?;
+    let heatmap = if args.scenic {
+        Heatmap::scenic_scores(&trees)
+    } else {
+        Heatmap::visibility(&trees)
+    };
+    let Some(path) = args.image else {
+        print!("{}", heatmap.render());
+        return Ok(ExitCode::SUCCESS);
+    };
+    let mut image = Vec::new();
+    if path.extension().is_some_and(|ext| ext == "pgm") {
+        heatmap.write_pgm(&mut image)
+    } else {
+        heatmap.write_ppm(&mut image)
+    }
+    .and_then(|()| std::fs::write(&path, image))
+    .map_err(|source| Error::Write {
+        path: path.display().to_string(),
+        source,
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn rope(args: RopeArgs, input_args: &InputArgs) -> Result<ExitCode> {
+    let selection: DaySelection = "9".parse().expect("day 9 is solved");
+    let raw_input = inputs(input_args, &selection).read(9)?;
+    let dimensions = if args.three_d {
+        Dimensions::Three
+    } else {
+        Dimensions::Two
+    };
+    let moves =
+        day09::parse_moves(&raw_input, dimensions).map_err(|err| err.locate(9, &raw_input))?;
+    let links = usize::from(args.knots) - 1;
+    let slack = match args.slack[..] {
+        [slack] => vec![slack; links],
+        _ if args.slack.len() == links => args.slack,
+        _ => Cli::command()
+            .error(
+                ErrorKind::WrongNumberOfValues,
+                format!(
+                    "--slack takes one value or one per knot after the head ({}), got {}",
+                    links,
+                    args.slack.len()
+                ),
+            )
+            .exit(),
+    };
+    let trace = Trace::record(&moves, Rope::with_slack(slack));
+    if args.visited {
+        print!(
+            "{}",
+            day09::render_visited(&trace.visited(), &trace.bounds())
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+    trace
+        .animate(&mut std::io::stdout().lock(), args.fps, args.size)
+        .map_err(|source| Error::Write {
+            path: "stdout".to_string(),
+            source,
+        })?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn cpu(args: CpuArgs, input_args: &InputArgs) -> Result<ExitCode> {
+    let raw_input = match &args.program {
+        Some(path) => std::fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.display().to_string(),
+            source,
+        })?,
+        None => {
+            let selection: DaySelection = "10".parse().expect("day 10 is solved");
+            inputs(input_args, &selection).read(10)?
+        }
+    };
+    let program = day10::assemble(&raw_input).map_err(|err| err.locate(10, &raw_input))?;
+    if args.disassemble {
+        print!("{}", day10::disassemble(&program));
+        return Ok(ExitCode::SUCCESS);
+    }
+    if args.debug {
+        debug(&program, args.max_cycles)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    let mut sampler = SignalSampler::new(&day10::TARGET_CYCLES);
+    let mut crt = Crt::new(40, 6);
+    let summary = if args.trace {
+        let mut tracer = Tracer::new(io::stdout().lock());
+        let summary =
+            Cpu::new(&program).run(args.max_cycles, &mut [&mut sampler, &mut crt, &mut tracer]);
+        // Hands back the stdout lock, which is released straight away.
+        let _ = tracer.finish().map_err(|source| Error::Io {
+            path: "stdout".to_string(),
+            source,
+        })?;
+        summary
+    } else {
+        Cpu::new(&program).run(args.max_cycles, &mut [&mut sampler, &mut crt])
+    };
+    for row in crt.screen() {
+        println!("{}", row.iter().collect::<String>());
+    }
+    match ocr::recognize(crt.screen()) {
+        Ok(letters) => println!("letters {}", letters),
+        Err(err) => println!("{}", err),
+    }
+    println!("signal strength {}", sampler.total());
+    println!("{}", summary);
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Reads debugger commands from stdin until `quit` or the end of input.
+fn debug(program: &[day10::Instruction], max_cycles: usize) -> Result<()> {
+    let to_error = |source| Error::Io {
+        path: "stdin".to_string(),
+        source,
+    };
+    let mut debugger = Debugger::new(program, max_cycles);
+    print!("{}", debugger.status());
+    let mut last = None;
+    let mut line = String::new();
+    loop {
+        print!("(cpu) ");
+        io::stdout().flush().map_err(to_error)?;
+        line.clear();
+        if io::stdin().read_line(&mut line).map_err(to_error)? == 0 {
+            println!();
+            return Ok(());
+        }
+        let command = if line.trim().is_empty() {
+            match last {
+                Some(command) => command,
+                None => continue,
+            }
+        } else {
+            match line.parse() {
+                Ok(command) => command,
+                Err(err) => {
+                    println!("{}", err);
+                    continue;
+                }
+            }
+        };
+        if command == DebugCommand::Quit {
+            return Ok(());
+        }
+        match debugger.execute(command) {
+            Ok(out) => print!("{}", out),
+            Err(err) => println!("{}", err),
+        }
+        last = Some(command);
+    }
 }
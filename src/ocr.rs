@@ -0,0 +1,136 @@
+use std::fmt;
+
+const GLYPH_WIDTH: usize = 4;
+const GLYPH_HEIGHT: usize = 6;
+/// Glyphs are drawn with one blank column after each.
+const GLYPH_SPACING: usize = GLYPH_WIDTH + 1;
+
+/// The letters of the 4×6 font used for Advent of Code screens. The rest of
+/// the alphabet has not turned up in a puzzle with this font.
+const FONT: [(char, [&str; GLYPH_HEIGHT]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...", "#...", ".#.#", "..#.", "..#.", "..#."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// The screen is not one glyph tall.
+    Height(usize),
+    /// The screen cannot be split into whole glyphs.
+    Width(usize),
+    /// The glyph at `index`, counting from 0, is not in the font. It is kept
+    /// drawn with `#` and `.` to show in the message.
+    UnknownGlyph { index: usize, glyph: Vec<String> },
+}
+
+impl std::error::Error for OcrError {}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OcrError::Height(height) => write!(
+                f,
+                "expected a screen {} pixels tall, found {}",
+                GLYPH_HEIGHT, height
+            ),
+            OcrError::Width(width) => write!(
+                f,
+                "expected a screen of {} pixel wide glyphs, found a width of {}",
+                GLYPH_SPACING, width
+            ),
+            OcrError::UnknownGlyph { index, glyph } => {
+                write!(f, "unrecognized glyph at letter {}:", index + 1)?;
+                for row in glyph {
+                    write!(f, "\n{}", row)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Reads the letters off a screen where `#` is lit and anything else is
+/// dark. The blank column after the last glyph may be left off.
+pub fn recognize(screen: &[Vec<char>]) -> Result<String, OcrError> {
+    if screen.len() != GLYPH_HEIGHT {
+        return Err(OcrError::Height(screen.len()));
+    }
+    let width = screen[0].len();
+    if screen.iter().any(|row| row.len() != width)
+        || (width + 1) % GLYPH_SPACING > 1
+        || width < GLYPH_WIDTH
+    {
+        return Err(OcrError::Width(width));
+    }
+    (0..(width + 1) / GLYPH_SPACING)
+        .map(|index| {
+            let start = index * GLYPH_SPACING;
+            let glyph: Vec<String> = screen
+                .iter()
+                .map(|row| {
+                    row[start..start + GLYPH_WIDTH]
+                        .iter()
+                        .map(|&pixel| if pixel == '#' { '#' } else { '.' })
+                        .collect()
+                })
+                .collect();
+            FONT.iter()
+                .find(|(_, pattern)| glyph.iter().eq(pattern.iter()))
+                .map(|&(letter, _)| letter)
+                .ok_or(OcrError::UnknownGlyph { index, glyph })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    #[test]
+    fn test_recognize() {
+        let crt = screen(&[
+            "###    ## #### ###  ###  #### ####  ##  ",
+            "#  #    # #    #  # #  # #    #    #  # ",
+            "#  #    # ###  #  # #  # ###  ###  #    ",
+            "###     # #    ###  ###  #    #    #    ",
+            "# #  #  # #    # #  #    #    #    #  # ",
+            "#  #  ##  #### #  # #    #### #     ##  ",
+        ]);
+        assert_eq!(recognize(&crt), Ok("RJERPEFC".to_string()));
+        let trimmed: Vec<_> = crt.iter().map(|row| row[..39].to_vec()).collect();
+        assert_eq!(recognize(&trimmed), Ok("RJERPEFC".to_string()));
+    }
+
+    #[test]
+    fn test_recognize_errors() {
+        let unknown = screen(&["#### ", "#  # ", "#### ", "#  # ", "#  # ", "#### "]);
+        let err = recognize(&unknown).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unrecognized glyph at letter 1:\n####\n#..#\n####\n#..#\n#..#\n####"
+        );
+        assert_eq!(recognize(&unknown[..5]), Err(OcrError::Height(5)));
+        let narrow: Vec<_> = unknown.iter().map(|row| row[..2].to_vec()).collect();
+        assert_eq!(recognize(&narrow), Err(OcrError::Width(2)));
+    }
+}
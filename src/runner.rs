@@ -0,0 +1,485 @@
+use crate::answer::Answer;
+use crate::days::{Day, DaySelection, Part};
+use crate::error::Result;
+use crate::inputs::Inputs;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::panic;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How the runner prints its results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Human readable, printed as each day finishes.
+    #[default]
+    Text,
+    /// A JSON array with one object per part.
+    Json,
+    /// A header row followed by one row per part.
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!(
+                "invalid format `{}`, expected text, json or csv",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    pub format: Format,
+    /// How many times to parse and solve each day. Reported times are the
+    /// median over all runs.
+    pub repeat: u32,
+    /// Number of threads to solve days on. Only when there are more threads
+    /// than days are the leftover ones used to solve the parts of a day at
+    /// the same time.
+    pub jobs: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            format: Format::Text,
+            repeat: 1,
+            jobs: 1,
+        }
+    }
+}
+
+/// Wall-clock time of a step repeated one or more times.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timing {
+    pub median: Duration,
+    pub min: Duration,
+}
+
+impl Timing {
+    /// Runs `f` `repeat` times (at least once), returning the last result.
+    fn measure<T>(repeat: u32, mut f: impl FnMut() -> T) -> (T, Timing) {
+        let mut times = Vec::new();
+        let result = loop {
+            let start = Instant::now();
+            let result = f();
+            times.push(start.elapsed());
+            if times.len() >= repeat as usize {
+                break result;
+            }
+        };
+        times.sort_unstable();
+        let timing = Timing {
+            median: times[times.len() / 2],
+            min: times[0],
+        };
+        (result, timing)
+    }
+}
+
+/// The answer to one part of a day, with how long it took to get there.
+#[derive(Debug, Clone)]
+pub struct PartResult {
+    pub day: u8,
+    pub part: Part,
+    pub answer: Answer,
+    pub runs: u32,
+    /// Time spent parsing the day's input, which is shared by both parts.
+    pub parse_time: Timing,
+    pub solve_time: Timing,
+}
+
+impl Serialize for PartResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut result = serializer.serialize_struct("PartResult", 8)?;
+        result.serialize_field("day", &self.day)?;
+        result.serialize_field("part", &self.part)?;
+        result.serialize_field("answer", &self.answer)?;
+        result.serialize_field("runs", &self.runs)?;
+        result.serialize_field("parse_time_ns", &self.parse_time.median.as_nanos())?;
+        result.serialize_field("parse_time_min_ns", &self.parse_time.min.as_nanos())?;
+        result.serialize_field("solve_time_ns", &self.solve_time.median.as_nanos())?;
+        result.serialize_field("solve_time_min_ns", &self.solve_time.min.as_nanos())?;
+        result.end()
+    }
+}
+
+/// Solves the selected parts of a single day. Every part but the last gets
+/// a thread of its own while `spare_threads` has one to lend, so that the
+/// caller decides how many threads run at once.
+pub fn solve_day(
+    day: &Day,
+    parts: &[Part],
+    inputs: &Inputs,
+    repeat: u32,
+    spare_threads: &AtomicUsize,
+) -> Result<Vec<PartResult>> {
+    let raw_input = inputs.read(day.number)?;
+    let (input, parse_time) = Timing::measure(repeat, || day.parse(&raw_input));
+    let input = input?;
+    let solve = |part| {
+        let (answer, solve_time) = Timing::measure(repeat, || day.solution.solve(&input, part));
+        PartResult {
+            day: day.number,
+            part,
+            answer,
+            runs: repeat.max(1),
+            parse_time,
+            solve_time,
+        }
+    };
+    let solve = &solve;
+    Ok(thread::scope(|scope| {
+        let borrow_thread = || {
+            spare_threads
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                .is_ok()
+        };
+        // Start every part that can have a thread before solving the rest
+        // here, so that they run alongside each other.
+        let handles: Vec<_> = parts
+            .iter()
+            .enumerate()
+            .map(|(i, &part)| {
+                (i + 1 < parts.len() && borrow_thread()).then(|| scope.spawn(move || solve(part)))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .zip(parts)
+            .map(|(handle, &part)| match handle {
+                Some(handle) => {
+                    let result = handle
+                        .join()
+                        .unwrap_or_else(|err| panic::resume_unwind(err));
+                    spare_threads.fetch_add(1, Ordering::Relaxed);
+                    result
+                }
+                None => solve(part),
+            })
+            .collect()
+    }))
+}
+
+/// Solves `days` on up to `jobs` threads, passing each day's results to
+/// `report` in day order. Threads left over once every day has a worker
+/// solve parts side by side. Stops at the first day that fails.
+fn solve_days(
+    days: &[&'static Day],
+    parts: &[Part],
+    inputs: &Inputs,
+    options: Options,
+    mut report: impl FnMut(&Day, Vec<PartResult>),
+) -> Result<()> {
+    if options.jobs <= 1 {
+        for &day in days {
+            report(
+                day,
+                solve_day(day, parts, inputs, options.repeat, &AtomicUsize::new(0))?,
+            );
+        }
+        return Ok(());
+    }
+
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let workers = options.jobs.min(days.len());
+    let spare_threads = AtomicUsize::new(options.jobs - workers);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let (next, stop, spare_threads) = (&next, &stop, &spare_threads);
+            scope.spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(&day) = days.get(i) else {
+                        break;
+                    };
+                    let result = solve_day(day, parts, inputs, options.repeat, spare_threads);
+                    if sender.send((i, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // Days finish out of order, so hold on to them until every earlier
+        // day has been reported.
+        let mut finished = BTreeMap::new();
+        let mut next_to_report = 0;
+        for (i, result) in receiver {
+            finished.insert(i, result);
+            while let Some(result) = finished.remove(&next_to_report) {
+                match result {
+                    Ok(results) => report(days[next_to_report], results),
+                    Err(err) => {
+                        stop.store(true, Ordering::Relaxed);
+                        return Err(err);
+                    }
+                }
+                next_to_report += 1;
+            }
+        }
+        Ok(())
+    })
+}
+
+pub fn run(
+    selection: &DaySelection,
+    parts: &[Part],
+    inputs: &Inputs,
+    options: Options,
+) -> Result<()> {
+    let start = Instant::now();
+    let days: Vec<&Day> = selection.days().collect();
+    let mut results = Vec::new();
+    solve_days(&days, parts, inputs, options, |day, day_results| {
+        if options.format == Format::Text {
+            print!("{}", to_text(day.number, &day_results));
+        }
+        results.extend(day_results);
+    })?;
+    match options.format {
+        Format::Text => print!("\n{}", summary(&results, start.elapsed())),
+        Format::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&results).expect("results are valid JSON")
+        ),
+        Format::Csv => print!("{}", to_csv(&results)),
+    }
+    Ok(())
+}
+
+fn to_text(day: u8, results: &[PartResult]) -> String {
+    let mut out = format!("Day {:02} ***********\n", day);
+    if let Some(first) = results.first() {
+        writeln!(
+            out,
+            "parse: {}",
+            format_timing(first.parse_time, first.runs)
+        )
+        .unwrap();
+    }
+    for result in results {
+        let time = format_timing(result.solve_time, result.runs);
+        match &result.answer {
+            Answer::Grid(rows) => {
+                writeln!(out, "part {}: ({})", result.part.number(), time).unwrap();
+                for row in rows {
+                    writeln!(out, "{}", row).unwrap();
+                }
+            }
+            answer => {
+                writeln!(out, "part {}: {} ({})", result.part.number(), answer, time).unwrap()
+            }
+        }
+    }
+    out
+}
+
+fn format_timing(timing: Timing, runs: u32) -> String {
+    if runs > 1 {
+        format!("median {:.2?}, min {:.2?}", timing.median, timing.min)
+    } else {
+        format!("{:.2?}", timing.median)
+    }
+}
+
+/// A table of median times per day, with each day's share of the total,
+/// followed by the wall-clock time of the whole run.
+fn summary(results: &[PartResult], wall_clock: Duration) -> String {
+    let mut days: Vec<(u8, Duration, [Option<Duration>; 2])> = Vec::new();
+    for result in results {
+        if days.last().map(|(day, ..)| *day) != Some(result.day) {
+            days.push((result.day, result.parse_time.median, [None; 2]));
+        }
+        let (_, _, parts) = days.last_mut().unwrap();
+        parts[result.part.number() as usize - 1] = Some(result.solve_time.median);
+    }
+    let day_total = |(_, parse, parts): &(u8, Duration, [Option<Duration>; 2])| -> Duration {
+        *parse + parts.iter().flatten().sum::<Duration>()
+    };
+    let total: Duration = days.iter().map(day_total).sum();
+
+    let runs = results.first().map_or(1, |result| result.runs);
+    let mut out = String::new();
+    if runs > 1 {
+        writeln!(out, "Median of {} runs", runs).unwrap();
+    }
+    writeln!(
+        out,
+        "{:<4} {:>10} {:>10} {:>10} {:>10} {:>6}",
+        "day", "parse", "part 1", "part 2", "total", "share"
+    )
+    .unwrap();
+    let cell =
+        |time: Option<Duration>| time.map_or(String::from("-"), |time| format!("{:.2?}", time));
+    for entry in &days {
+        let (day, parse, [part_1, part_2]) = *entry;
+        let day_total = day_total(entry);
+        writeln!(
+            out,
+            "{:<4} {:>10} {:>10} {:>10} {:>10} {:>5.1}%",
+            format!("{:02}", day),
+            cell(Some(parse)),
+            cell(part_1),
+            cell(part_2),
+            cell(Some(day_total)),
+            100.0 * day_total.as_secs_f64() / total.as_secs_f64().max(f64::MIN_POSITIVE),
+        )
+        .unwrap();
+    }
+    writeln!(out, "{:<4} {:>43}", "all", cell(Some(total))).unwrap();
+    writeln!(out, "wall clock {:.2?}", wall_clock).unwrap();
+    out
+}
+
+fn to_csv(results: &[PartResult]) -> String {
+    let mut out = String::from(
+        "day,part,answer,runs,parse_time_ns,parse_time_min_ns,solve_time_ns,solve_time_min_ns\n",
+    );
+    for result in results {
+        let answer = match &result.answer {
+            Answer::Unsolved => String::new(),
+            answer => answer.to_string(),
+        };
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{}",
+            result.day,
+            result.part.number(),
+            csv_field(&answer),
+            result.runs,
+            result.parse_time.median.as_nanos(),
+            result.parse_time.min.as_nanos(),
+            result.solve_time.median.as_nanos(),
+            result.solve_time.min.as_nanos()
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Quotes a field if it contains a delimiter, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(part: Part, answer: Answer) -> PartResult {
+        PartResult {
+            day: 10,
+            part,
+            answer,
+            runs: 3,
+            parse_time: Timing {
+                median: Duration::from_nanos(1500),
+                min: Duration::from_nanos(1000),
+            },
+            solve_time: Timing {
+                median: Duration::from_nanos(20),
+                min: Duration::from_nanos(15),
+            },
+        }
+    }
+
+    #[test]
+    fn test_json() {
+        let results = [
+            result(Part::One, Answer::Number(13140)),
+            result(Part::Two, Answer::Grid(vec!["##..".into(), "#..#".into()])),
+        ];
+        assert_eq!(
+            serde_json::to_string(&results).unwrap(),
+            concat!(
+                r###"[{"day":10,"part":1,"answer":13140,"runs":3,"parse_time_ns":1500,"###,
+                r###""parse_time_min_ns":1000,"solve_time_ns":20,"solve_time_min_ns":15},"###,
+                r###"{"day":10,"part":2,"answer":["##..","#..#"],"runs":3,"parse_time_ns":1500,"###,
+                r###""parse_time_min_ns":1000,"solve_time_ns":20,"solve_time_min_ns":15}]"###
+            )
+        );
+    }
+
+    #[test]
+    fn test_measure() {
+        let mut calls = 0;
+        let (result, _) = Timing::measure(5, || {
+            calls += 1;
+            calls
+        });
+        assert_eq!((result, calls), (5, 5));
+        let (_, timing) = Timing::measure(0, || ());
+        assert!(timing.min <= timing.median);
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let days: Vec<&Day> = "1..13".parse::<DaySelection>().unwrap().days().collect();
+        let inputs = Inputs::new("src/days/examples");
+        let solve = |jobs| {
+            let mut answers = Vec::new();
+            let options = Options {
+                jobs,
+                ..Options::default()
+            };
+            solve_days(&days, &Part::BOTH, &inputs, options, |day, results| {
+                answers.extend(
+                    results
+                        .into_iter()
+                        .map(|result| (day.number, result.answer)),
+                )
+            })
+            .unwrap();
+            answers
+        };
+        assert_eq!(solve(4), solve(1));
+        assert_eq!(solve(30), solve(1));
+    }
+
+    #[test]
+    fn test_spare_threads() {
+        let day = "1".parse::<DaySelection>().unwrap().days().next().unwrap();
+        let inputs = Inputs::new("src/days/examples");
+        let spare_threads = AtomicUsize::new(1);
+        let results = solve_day(day, &Part::BOTH, &inputs, 1, &spare_threads).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(spare_threads.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_csv() {
+        let results = [
+            result(Part::One, Answer::Text(String::from("a,\"b\""))),
+            result(Part::Two, Answer::Unsolved),
+        ];
+        assert_eq!(
+            to_csv(&results),
+            "day,part,answer,runs,parse_time_ns,parse_time_min_ns,solve_time_ns,solve_time_min_ns\n\
+             10,1,\"a,\"\"b\"\"\",3,1500,1000,20,15\n\
+             10,2,,3,1500,1000,20,15\n"
+        );
+    }
+}
@@ -1,11 +1,23 @@
+use crate::error::{Error, Result};
 use std::{
     fs::File,
-    io::{Error, Read},
+    io::{self, Read},
+    path::Path,
 };
 
-pub fn read_input(path: &str) -> Result<String, Error> {
-    let mut file_handle = File::open(path)?;
+pub fn read_input(path: &Path) -> Result<String> {
+    let to_error = |source| Error::Io {
+        path: path.display().to_string(),
+        source,
+    };
+    let mut file_handle = File::open(path).map_err(to_error)?;
     let mut content = String::new();
-    file_handle.read_to_string(&mut content)?;
+    file_handle.read_to_string(&mut content).map_err(to_error)?;
+    Ok(content)
+}
+
+pub fn read_stdin() -> io::Result<String> {
+    let mut content = String::new();
+    io::stdin().read_to_string(&mut content)?;
     Ok(content)
 }
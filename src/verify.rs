@@ -0,0 +1,232 @@
+use crate::days::{DaySelection, Part};
+use crate::error::{Error, Result};
+use crate::inputs::Inputs;
+use crate::util;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// Expected answers keyed by day and part, read from a TOML file such as:
+///
+/// ```toml
+/// [day01]
+/// part1 = 67658
+/// part2 = 200158
+///
+/// [day10]
+/// part2 = "RJERPEFC"
+/// ```
+///
+/// Multi-line answers are written as multi-line strings. Trailing whitespace
+/// on each line is ignored when comparing.
+#[derive(Debug, Default)]
+pub struct AnswerFile {
+    answers: BTreeMap<(u8, u8), String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DayAnswers {
+    part1: Option<Expected>,
+    part2: Option<Expected>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Expected {
+    Number(i64),
+    Text(String),
+}
+
+impl AnswerFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = util::read_input(path)?;
+        Self::parse(&content).map_err(|reason| Error::AnswerFile {
+            path: path.display().to_string(),
+            reason,
+        })
+    }
+
+    pub fn parse(content: &str) -> std::result::Result<Self, String> {
+        let days: BTreeMap<String, DayAnswers> =
+            toml::from_str(content).map_err(|err| err.message().to_owned())?;
+        let mut answers = BTreeMap::new();
+        for (key, day_answers) in days {
+            let day: u8 = key
+                .strip_prefix("day")
+                .and_then(|day| day.parse().ok())
+                .ok_or_else(|| format!("invalid table `[{}]`, expected e.g. `[day01]`", key))?;
+            for (part, expected) in [(1, day_answers.part1), (2, day_answers.part2)] {
+                let expected = match expected {
+                    Some(Expected::Number(num)) => num.to_string(),
+                    Some(Expected::Text(text)) => text,
+                    None => continue,
+                };
+                answers.insert((day, part), expected);
+            }
+        }
+        Ok(Self { answers })
+    }
+
+    pub fn expected(&self, day: u8, part: Part) -> Option<&str> {
+        self.answers.get(&(day, part.number())).map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Fail {
+        expected: String,
+        actual: String,
+    },
+    /// No expected answer has been recorded for this part.
+    Missing {
+        actual: String,
+    },
+    /// The input could not be read or parsed.
+    Error(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub day: u8,
+    pub part: Part,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    fn count(&self, pred: impl Fn(&Outcome) -> bool) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| pred(&check.outcome))
+            .count()
+    }
+
+    /// Whether every recorded answer matched and every input could be solved.
+    pub fn is_success(&self) -> bool {
+        self.count(|outcome| matches!(outcome, Outcome::Fail { .. } | Outcome::Error(_))) == 0
+    }
+}
+
+/// Solves the selected days and compares each part against `answers`.
+pub fn verify(
+    selection: &DaySelection,
+    parts: &[Part],
+    inputs: &Inputs,
+    answers: &AnswerFile,
+) -> Report {
+    let mut report = Report::default();
+    for day in selection.days() {
+        let input = inputs
+            .read(day.number)
+            .and_then(|raw_input| day.parse(&raw_input));
+        for &part in parts {
+            let outcome = match &input {
+                Ok(input) => {
+                    let actual = normalize(&day.solution.solve(input, part).to_string());
+                    match answers.expected(day.number, part).map(normalize) {
+                        Some(expected) if expected == actual => Outcome::Pass,
+                        Some(expected) => Outcome::Fail { expected, actual },
+                        None => Outcome::Missing { actual },
+                    }
+                }
+                Err(err) => Outcome::Error(err.to_string()),
+            };
+            report.checks.push(Check {
+                day: day.number,
+                part,
+                outcome,
+            });
+        }
+    }
+    report
+}
+
+fn normalize(answer: &str) -> String {
+    let lines: Vec<&str> = answer.lines().map(str::trim_end).collect();
+    lines.join("\n").trim_matches('\n').to_owned()
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day {:02} part {}: ", self.day, self.part.number())?;
+        match &self.outcome {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Missing { actual } if actual.contains('\n') => {
+                write!(f, "missing, got:\n{}", actual)
+            }
+            Outcome::Missing { actual } => write!(f, "missing, got {}", actual),
+            Outcome::Error(err) => write!(f, "error: {}", err),
+            Outcome::Fail { expected, actual } => {
+                writeln!(f, "FAIL")?;
+                write_diff(f, expected, actual)
+            }
+        }
+    }
+}
+
+/// Writes a line by line diff, marking expected lines with `-` and actual
+/// lines with `+`.
+fn write_diff(f: &mut fmt::Formatter<'_>, expected: &str, actual: &str) -> fmt::Result {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => writeln!(f, "    {}", e)?,
+            (e, a) => {
+                if let Some(e) = e {
+                    writeln!(f, "  - {}", e)?;
+                }
+                if let Some(a) = a {
+                    writeln!(f, "  + {}", a)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(f, "{}", check.to_string().trim_end())?;
+        }
+        write!(
+            f,
+            "{} passed, {} failed, {} missing, {} errors",
+            self.count(|outcome| *outcome == Outcome::Pass),
+            self.count(|outcome| matches!(outcome, Outcome::Fail { .. })),
+            self.count(|outcome| matches!(outcome, Outcome::Missing { .. })),
+            self.count(|outcome| matches!(outcome, Outcome::Error(_))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answer_file() {
+        let answers = AnswerFile::parse(
+            "[day01]\npart1 = 67658\npart2 = \"200158\"\n\n[day10]\npart2 = \"\"\"\n## \n#  \n\"\"\"\n",
+        )
+        .unwrap();
+        assert_eq!(answers.expected(1, Part::One), Some("67658"));
+        assert_eq!(answers.expected(1, Part::Two), Some("200158"));
+        assert_eq!(answers.expected(5, Part::One), None);
+        assert_eq!(
+            answers.expected(10, Part::Two).map(normalize),
+            Some(String::from("##\n#"))
+        );
+        assert!(AnswerFile::parse("[one]\npart1 = 1\n").is_err());
+        assert!(AnswerFile::parse("[day01]\npart3 = 1\n").is_err());
+    }
+}